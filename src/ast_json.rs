@@ -0,0 +1,239 @@
+use crate::{
+    expr::{
+        Assign, Binary, Call, Comma, Expr, Grouping, Increment, Lambda, Literal, Ternary, Unary,
+        Variable, Visitor,
+    },
+    token::{LiteralType, Token},
+};
+
+pub struct AstJson {}
+
+impl AstJson {
+    pub fn print(expr: Expr) -> String {
+        AstJson {}.visit(expr)
+    }
+
+    fn object(node_type: &str, fields: Vec<(&str, String)>) -> String {
+        let mut result = String::new();
+
+        result.push_str("{\"type\":");
+        result.push_str(&AstJson::string(node_type));
+
+        for (name, value) in fields {
+            result.push(',');
+            result.push_str(&AstJson::string(name));
+            result.push(':');
+            result.push_str(&value);
+        }
+
+        result.push('}');
+
+        result
+    }
+
+    fn token(token: &Token) -> String {
+        AstJson::object(
+            &format!("{:?}", token.token_type),
+            vec![
+                ("lexeme", AstJson::string(&token.lexeme)),
+                ("line", token.line.to_string()),
+                ("column", token.column.to_string()),
+            ],
+        )
+    }
+
+    fn literal(value: &LiteralType) -> String {
+        match value {
+            LiteralType::String(s) => AstJson::string(s),
+            LiteralType::Number(n) if n.is_finite() => n.to_string(),
+            LiteralType::Number(n) => AstJson::string(&n.to_string()),
+            LiteralType::Bool(b) => b.to_string(),
+            LiteralType::Nil | LiteralType::None => String::from("null"),
+            LiteralType::NativeFunction(_) | LiteralType::Function(_) => {
+                AstJson::string(&value.to_string())
+            }
+        }
+    }
+
+    fn string(value: &str) -> String {
+        let mut result = String::from("\"");
+
+        for c in value.chars() {
+            match c {
+                '"' => result.push_str("\\\""),
+                '\\' => result.push_str("\\\\"),
+                '\n' => result.push_str("\\n"),
+                '\r' => result.push_str("\\r"),
+                '\t' => result.push_str("\\t"),
+                c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+                c => result.push(c),
+            }
+        }
+
+        result.push('"');
+
+        result
+    }
+}
+
+impl Visitor<String> for AstJson {
+    fn visit(&mut self, expr: Expr) -> String {
+        match expr {
+            Expr::Binary(binary) => {
+                let Binary {
+                    left,
+                    operator,
+                    right,
+                } = *binary;
+                AstJson::object(
+                    "Binary",
+                    vec![
+                        ("left", self.visit(*left)),
+                        ("operator", AstJson::token(&operator)),
+                        ("right", self.visit(*right)),
+                    ],
+                )
+            }
+            Expr::Grouping(grouping) => {
+                let Grouping { expression } = *grouping;
+                AstJson::object("Grouping", vec![("expression", self.visit(*expression))])
+            }
+            Expr::Literal(literal) => {
+                let Literal { value } = *literal;
+                AstJson::object("Literal", vec![("value", AstJson::literal(&value))])
+            }
+            Expr::Unary(unary) => {
+                let Unary { operator, right } = *unary;
+                AstJson::object(
+                    "Unary",
+                    vec![
+                        ("operator", AstJson::token(&operator)),
+                        ("right", self.visit(*right)),
+                    ],
+                )
+            }
+            Expr::Comma(comma) => {
+                let Comma { left, right } = *comma;
+                AstJson::object(
+                    "Comma",
+                    vec![("left", self.visit(*left)), ("right", self.visit(*right))],
+                )
+            }
+            Expr::Ternary(ternary) => {
+                let Ternary {
+                    condition,
+                    then_branch,
+                    else_branch,
+                } = *ternary;
+                AstJson::object(
+                    "Ternary",
+                    vec![
+                        ("condition", self.visit(*condition)),
+                        ("then_branch", self.visit(*then_branch)),
+                        ("else_branch", self.visit(*else_branch)),
+                    ],
+                )
+            }
+            Expr::Call(call) => {
+                let Call {
+                    callee,
+                    paren,
+                    arguments,
+                } = *call;
+                let arguments = arguments
+                    .into_iter()
+                    .map(|argument| self.visit(argument))
+                    .collect::<Vec<_>>()
+                    .join(",");
+
+                AstJson::object(
+                    "Call",
+                    vec![
+                        ("callee", self.visit(*callee)),
+                        ("paren", AstJson::token(&paren)),
+                        ("arguments", format!("[{}]", arguments)),
+                    ],
+                )
+            }
+            Expr::Variable(variable) => {
+                let Variable { name } = *variable;
+                AstJson::object("Variable", vec![("name", AstJson::token(&name))])
+            }
+            Expr::Lambda(lambda) => {
+                let Lambda {
+                    params,
+                    arrow,
+                    body,
+                } = *lambda;
+                let params = params
+                    .iter()
+                    .map(AstJson::token)
+                    .collect::<Vec<_>>()
+                    .join(",");
+
+                AstJson::object(
+                    "Lambda",
+                    vec![
+                        ("params", format!("[{}]", params)),
+                        ("arrow", AstJson::token(&arrow)),
+                        ("body", self.visit(*body)),
+                    ],
+                )
+            }
+            Expr::Assign(assign) => {
+                let Assign {
+                    name,
+                    operator,
+                    value,
+                } = *assign;
+                AstJson::object(
+                    "Assign",
+                    vec![
+                        ("name", AstJson::token(&name)),
+                        ("operator", AstJson::token(&operator)),
+                        ("value", self.visit(*value)),
+                    ],
+                )
+            }
+            Expr::Increment(increment) => {
+                let Increment {
+                    name,
+                    operator,
+                    prefix,
+                } = *increment;
+                AstJson::object(
+                    "Increment",
+                    vec![
+                        ("name", AstJson::token(&name)),
+                        ("operator", AstJson::token(&operator)),
+                        ("prefix", prefix.to_string()),
+                    ],
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::token_type::TokenType;
+
+    use super::*;
+
+    #[test]
+    fn test_ast_json() {
+        let expr = Expr::Unary(Box::new(Unary {
+            operator: Token::new(TokenType::Minus, "-".to_string(), LiteralType::None, 1, 1),
+            right: Box::new(Expr::Literal(Box::new(Literal {
+                value: LiteralType::String("a \"b\"".to_string()),
+            }))),
+        }));
+
+        let result = AstJson::print(expr);
+
+        assert_eq!(
+            result,
+            "{\"type\":\"Unary\",\"operator\":{\"type\":\"Minus\",\"lexeme\":\"-\",\"line\":1,\"column\":1},\"right\":{\"type\":\"Literal\",\"value\":\"a \\\"b\\\"\"}}"
+        );
+    }
+}
@@ -1,55 +1,96 @@
-use crate::expr::{Binary, Comma, Expr, Grouping, Literal, Ternary, Unary, Visitor};
+use crate::expr::{Assign, Binary, Call, Comma, Expr, Increment, Lambda, Ternary, Unary, Visitor};
 
 pub struct AstPrinter {}
 
 impl AstPrinter {
     pub fn print(expr: Expr) -> String {
-        let output = AstPrinter::visit(expr);
-        println!("{}", output);
-
-        output
+        AstPrinter {}.visit(expr)
     }
 
-    fn parenthesize(name: &str, exprs: Vec<Box<Expr>>) -> String {
+    fn parenthesize(&mut self, name: &str, exprs: Vec<Expr>) -> String {
         let mut result = String::new();
 
-        result.push_str("(");
+        result.push('(');
         result.push_str(name);
 
         for expr in exprs {
-            result.push_str(" ");
-            result.push_str(&AstPrinter::visit(*expr));
+            result.push(' ');
+            result.push_str(&self.visit(expr));
         }
 
-        result.push_str(")");
+        result.push(')');
 
         result
     }
 }
 
 impl Visitor<String> for AstPrinter {
-    fn visit(expr: Expr) -> String {
+    fn visit(&mut self, expr: Expr) -> String {
         match expr {
-            Expr::Binary(Binary {
-                left,
-                operator,
-                right,
-            }) => AstPrinter::parenthesize(&operator.lexeme, vec![left, right]),
-            Expr::Grouping(Grouping { expression }) => {
-                AstPrinter::parenthesize("group", vec![expression])
+            Expr::Binary(binary) => {
+                let Binary {
+                    left,
+                    operator,
+                    right,
+                } = *binary;
+                self.parenthesize(&operator.lexeme, vec![*left, *right])
+            }
+            Expr::Grouping(grouping) => self.parenthesize("group", vec![*grouping.expression]),
+            Expr::Literal(literal) => format!("{}", literal.value),
+            Expr::Unary(unary) => {
+                let Unary { operator, right } = *unary;
+                self.parenthesize(&operator.lexeme, vec![*right])
+            }
+            Expr::Comma(comma) => {
+                let Comma { left, right } = *comma;
+                self.parenthesize("sequence", vec![*left, *right])
+            }
+            Expr::Ternary(ternary) => {
+                let Ternary {
+                    condition,
+                    then_branch,
+                    else_branch,
+                } = *ternary;
+                self.parenthesize("ternary", vec![*condition, *then_branch, *else_branch])
             }
-            Expr::Literal(Literal { value }) => format!("{}", value),
-            Expr::Unary(Unary { operator, right }) => {
-                AstPrinter::parenthesize(&operator.lexeme, vec![right])
+            Expr::Call(call) => {
+                let Call {
+                    callee, arguments, ..
+                } = *call;
+                let mut exprs = vec![*callee];
+                exprs.extend(arguments);
+                self.parenthesize("call", exprs)
             }
-            Expr::Comma(Comma { left, right }) => {
-                AstPrinter::parenthesize("sequence", vec![left, right])
+            Expr::Variable(variable) => variable.name.lexeme,
+            Expr::Lambda(lambda) => {
+                let Lambda { params, body, .. } = *lambda;
+                let params: Vec<String> = params.into_iter().map(|param| param.lexeme).collect();
+                self.parenthesize(&format!("lambda ({})", params.join(" ")), vec![*body])
+            }
+            Expr::Assign(assign) => {
+                let Assign {
+                    name,
+                    operator,
+                    value,
+                } = *assign;
+                self.parenthesize(
+                    &format!("{} {}", operator.lexeme, name.lexeme),
+                    vec![*value],
+                )
+            }
+            Expr::Increment(increment) => {
+                let Increment {
+                    name,
+                    operator,
+                    prefix,
+                } = *increment;
+
+                if prefix {
+                    format!("({} {})", operator.lexeme, name.lexeme)
+                } else {
+                    format!("({} {})", name.lexeme, operator.lexeme)
+                }
             }
-            Expr::Ternary(Ternary {
-                condition,
-                then_branch,
-                else_branch,
-            }) => AstPrinter::parenthesize("ternary", vec![condition, then_branch, else_branch]),
         }
     }
 }
@@ -57,6 +98,7 @@ impl Visitor<String> for AstPrinter {
 #[cfg(test)]
 mod tests {
     use crate::{
+        expr::{Grouping, Literal},
         token::{LiteralType, Token},
         token_type::TokenType,
     };
@@ -65,20 +107,20 @@ mod tests {
 
     #[test]
     fn test_ast_printer() {
-        let expr = Expr::Binary(Binary {
-            left: Box::new(Expr::Unary(Unary {
-                operator: Token::new(TokenType::Minus, "-".to_string(), LiteralType::None, 1),
-                right: Box::new(Expr::Literal(Literal {
+        let expr = Expr::Binary(Box::new(Binary {
+            left: Box::new(Expr::Unary(Box::new(Unary {
+                operator: Token::new(TokenType::Minus, "-".to_string(), LiteralType::None, 1, 1),
+                right: Box::new(Expr::Literal(Box::new(Literal {
                     value: LiteralType::Number(123.0),
-                })),
-            })),
-            operator: Token::new(TokenType::Star, "*".to_string(), LiteralType::None, 1),
-            right: Box::new(Expr::Grouping(Grouping {
-                expression: Box::new(Expr::Literal(Literal {
+                }))),
+            }))),
+            operator: Token::new(TokenType::Star, "*".to_string(), LiteralType::None, 1, 1),
+            right: Box::new(Expr::Grouping(Box::new(Grouping {
+                expression: Box::new(Expr::Literal(Box::new(Literal {
                     value: LiteralType::Number(45.67),
-                })),
-            })),
-        });
+                }))),
+            }))),
+        }));
 
         let result = AstPrinter::print(expr);
 
@@ -0,0 +1,67 @@
+use clap::ValueEnum;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+pub enum Compat {
+    #[default]
+    Rlox,
+    Jlox,
+}
+
+/// Formats a number the way `Double.toString` does in Java, after jlox strips
+/// the trailing `.0` of integral values.
+pub fn java_number(value: f64) -> String {
+    if value.is_nan() {
+        return String::from("NaN");
+    }
+
+    if value.is_infinite() {
+        return String::from(if value > 0.0 { "Infinity" } else { "-Infinity" });
+    }
+
+    let magnitude = value.abs();
+
+    let text = if magnitude == 0.0 || (1e-3..1e7).contains(&magnitude) {
+        let text = value.to_string();
+        if text.contains('.') {
+            text
+        } else {
+            format!("{}.0", text)
+        }
+    } else {
+        let text = format!("{:e}", value);
+        let (mantissa, exponent) = text.split_once('e').unwrap();
+        if mantissa.contains('.') {
+            format!("{}E{}", mantissa, exponent)
+        } else {
+            format!("{}.0E{}", mantissa, exponent)
+        }
+    };
+
+    match text.strip_suffix(".0") {
+        Some(integral) => integral.to_string(),
+        None => text,
+    }
+}
+
+/// Java's `Double.equals`, which jlox uses for `==`: NaN equals itself and
+/// `0` differs from `-0`.
+pub fn java_number_equals(left: f64, right: f64) -> bool {
+    (left.is_nan() && right.is_nan()) || left.to_bits() == right.to_bits()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_java_number() {
+        assert_eq!(java_number(2.0), "2");
+        assert_eq!(java_number(-0.0), "-0");
+        assert_eq!(java_number(123.456), "123.456");
+        assert_eq!(java_number(0.1 + 0.2), "0.30000000000000004");
+        assert_eq!(java_number(1e7), "1.0E7");
+        assert_eq!(java_number(1.5e-7), "1.5E-7");
+        assert_eq!(java_number(f64::INFINITY), "Infinity");
+        assert_eq!(java_number(f64::NAN), "NaN");
+    }
+}
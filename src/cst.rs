@@ -0,0 +1,98 @@
+use std::fmt::{self, Display};
+
+use crate::token::Token;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SyntaxKind {
+    Script,
+    Binary,
+    Grouping,
+    Literal,
+    Unary,
+    Comma,
+    Ternary,
+    Call,
+    Variable,
+    Lambda,
+    Assign,
+    Increment,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(Token),
+}
+
+/// A node of the concrete syntax tree. Unlike `Expr`, it keeps every token
+/// of the source, with its trivia, so printing it gives back the source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxNode {
+    pub kind: SyntaxKind,
+    pub children: Vec<SyntaxElement>,
+}
+
+impl SyntaxNode {
+    pub fn tokens(&self) -> Vec<&Token> {
+        self.children
+            .iter()
+            .flat_map(|child| match child {
+                SyntaxElement::Node(node) => node.tokens(),
+                SyntaxElement::Token(token) => vec![token],
+            })
+            .collect()
+    }
+}
+
+impl Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for token in self.tokens() {
+            write!(f, "{}", token.full_text())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// What the parser did, in order, recorded while it builds the `Expr`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    Start(SyntaxKind),
+    Token,
+    Finish,
+}
+
+/// Replays the parser's events over its tokens. Tokens the parser never
+/// consumed, such as the EOF, go to the root so that no text is lost.
+pub fn build(events: Vec<Event>, tokens: Vec<Token>) -> SyntaxNode {
+    let mut tokens = tokens.into_iter();
+    let mut stack = vec![SyntaxNode {
+        kind: SyntaxKind::Script,
+        children: Vec::new(),
+    }];
+
+    for event in events {
+        match event {
+            Event::Start(kind) => stack.push(SyntaxNode {
+                kind,
+                children: Vec::new(),
+            }),
+            Event::Token => {
+                let token = tokens.next().expect("more token events than tokens");
+                let node = stack.last_mut().unwrap();
+                node.children.push(SyntaxElement::Token(token));
+            }
+            Event::Finish => {
+                let node = stack.pop().unwrap();
+                let parent = stack.last_mut().expect("unbalanced syntax events");
+                parent.children.push(SyntaxElement::Node(node));
+            }
+        }
+    }
+
+    let mut root = stack.pop().unwrap();
+    assert!(stack.is_empty(), "unbalanced syntax events");
+    root.children.extend(tokens.map(SyntaxElement::Token));
+
+    root
+}
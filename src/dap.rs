@@ -0,0 +1,502 @@
+use std::{
+    cell::RefCell,
+    fs,
+    io::{self, BufRead, Write},
+    rc::Rc,
+};
+
+use serde_json::{Value, json};
+
+use crate::{
+    debugger::{self, Hook, Mode, Stepper, StopReason},
+    error_handler,
+    interpreter::Interpreter,
+    lox::Lox,
+    messages::{Locale, Message},
+    protocol::{self, read_message},
+};
+
+const THREAD_ID: u64 = 1;
+const GLOBALS_REFERENCE: u64 = 1;
+const LOCALS_REFERENCE: u64 = 2;
+
+/// Serves the Debug Adapter Protocol on `input` and `output` until the client
+/// disconnects, answering in `locale`. `new_lox` creates the interpreter for
+/// the launched script from the script's arguments.
+///
+/// Lox has no statements yet, so the adapter steps through expressions like
+/// the `debug` console does: the stack frames are the expressions being
+/// evaluated, and their scopes are the locals of the lambda being called and
+/// the globals.
+pub fn serve(
+    locale: Locale,
+    new_lox: impl Fn(Vec<String>) -> Lox,
+    input: impl BufRead + 'static,
+    output: impl Write + 'static,
+) -> io::Result<()> {
+    let session = Rc::new(RefCell::new(Session {
+        input: Box::new(input),
+        output: Box::new(output),
+        seq: 0,
+        locale,
+        launch: None,
+        stepper: Stepper::new(Mode::Continue),
+        has_stopped: false,
+        disconnected: false,
+    }));
+
+    loop {
+        let setup = {
+            let mut session = session.borrow_mut();
+            let Some(request) = session.read_request()? else {
+                return Ok(());
+            };
+            session.setup(&request)?
+        };
+
+        match setup {
+            Setup::Wait => {}
+            Setup::Run(launch) => run(&session, new_lox(launch.args.clone()), &launch)?,
+            Setup::Disconnect => return Ok(()),
+        }
+    }
+}
+
+/// Runs the launched script, with `session` answering requests whenever it
+/// pauses and receiving its output as events.
+fn run(session: &Rc<RefCell<Session>>, mut lox: Lox, launch: &Launch) -> io::Result<()> {
+    lox.set_file(Some(&launch.program));
+    lox.set_output(Output::new(session, "stdout"));
+    lox.set_error_output(Output::new(session, "stderr"));
+
+    let adapter = Adapter {
+        session: Rc::clone(session),
+    };
+    let diagnostics = lox.debug(&launch.source, Box::new(adapter));
+
+    let mut session = session.borrow_mut();
+    if session.disconnected {
+        return Ok(());
+    }
+
+    let exit_code = error_handler::exit_code(&diagnostics);
+    session.event("exited", json!({ "exitCode": exit_code }))?;
+    session.event("terminated", json!({}))
+}
+
+#[derive(Debug, Clone)]
+struct Launch {
+    program: String,
+    source: String,
+    args: Vec<String>,
+}
+
+enum Setup {
+    Wait,
+    Run(Launch),
+    Disconnect,
+}
+
+struct Session {
+    input: Box<dyn BufRead>,
+    output: Box<dyn Write>,
+    seq: u64,
+    locale: Locale,
+    launch: Option<Launch>,
+    stepper: Stepper,
+    has_stopped: bool,
+    disconnected: bool,
+}
+
+impl Session {
+    /// Handles a request sent before the script runs.
+    fn setup(&mut self, request: &Value) -> io::Result<Setup> {
+        match request["command"].as_str().unwrap_or_default() {
+            "initialize" => {
+                let capabilities = json!({
+                    "supportsConfigurationDoneRequest": true,
+                    "supportsSetVariable": true,
+                });
+                self.respond(request, capabilities)?;
+                self.event("initialized", json!({}))?;
+            }
+            "launch" => self.launch(request)?,
+            "setBreakpoints" => self.set_breakpoints(request)?,
+            "threads" => self.threads(request)?,
+            "configurationDone" => match self.launch.clone() {
+                Some(launch) => {
+                    self.respond(request, json!({}))?;
+                    return Ok(Setup::Run(launch));
+                }
+                None => self.fail(request, Message::NoScriptLaunched, &[])?,
+            },
+            "disconnect" => {
+                self.respond(request, json!({}))?;
+                return Ok(Setup::Disconnect);
+            }
+            command => self.fail(request, Message::NeedsPausedScript, &[command])?,
+        }
+
+        Ok(Setup::Wait)
+    }
+
+    /// Answers requests while the script is paused, until one resumes it.
+    /// Returns whether the script should go on.
+    fn pause(&mut self, interpreter: &mut Interpreter, reason: StopReason) -> io::Result<bool> {
+        let reason = match reason {
+            StopReason::Breakpoint => "breakpoint",
+            StopReason::Step if !self.has_stopped => "entry",
+            StopReason::Step => "step",
+        };
+        self.has_stopped = true;
+
+        self.event(
+            "stopped",
+            json!({ "reason": reason, "threadId": THREAD_ID, "allThreadsStopped": true }),
+        )?;
+
+        loop {
+            let Some(request) = self.read_request()? else {
+                self.disconnected = true;
+                return Ok(false);
+            };
+
+            let depth = interpreter.frames().len();
+            let mode = match request["command"].as_str().unwrap_or_default() {
+                "threads" => {
+                    self.threads(&request)?;
+                    continue;
+                }
+                "setBreakpoints" => {
+                    self.set_breakpoints(&request)?;
+                    continue;
+                }
+                "stackTrace" => {
+                    self.stack_trace(&request, interpreter)?;
+                    continue;
+                }
+                "scopes" => {
+                    let scopes = json!({
+                        "scopes": [
+                            {
+                                "name": self.locale.text(Message::LocalsScope, &[]),
+                                "variablesReference": LOCALS_REFERENCE,
+                                "expensive": false,
+                            },
+                            {
+                                "name": self.locale.text(Message::GlobalsScope, &[]),
+                                "variablesReference": GLOBALS_REFERENCE,
+                                "expensive": false,
+                            },
+                        ],
+                    });
+                    self.respond(&request, scopes)?;
+                    continue;
+                }
+                "variables" => {
+                    self.variables(&request, interpreter)?;
+                    continue;
+                }
+                "setVariable" => {
+                    self.set_variable(&request, interpreter)?;
+                    continue;
+                }
+                "continue" => Mode::Continue,
+                "next" => Mode::Next(depth),
+                "stepIn" => Mode::Step,
+                "stepOut" => Mode::Out(depth),
+                "disconnect" => {
+                    self.respond(&request, json!({}))?;
+                    self.disconnected = true;
+                    return Ok(false);
+                }
+                command => {
+                    self.fail(&request, Message::UnknownRequest, &[command])?;
+                    continue;
+                }
+            };
+
+            let body = match mode {
+                Mode::Continue => json!({ "allThreadsContinued": true }),
+                _ => json!({}),
+            };
+            self.respond(&request, body)?;
+            self.stepper.mode = mode;
+
+            return Ok(true);
+        }
+    }
+
+    fn launch(&mut self, request: &Value) -> io::Result<()> {
+        let arguments = &request["arguments"];
+        let Some(program) = arguments["program"].as_str() else {
+            return self.fail(request, Message::LaunchNeedsProgram, &[]);
+        };
+
+        let source = match fs::read_to_string(program) {
+            Ok(source) => source,
+            Err(err) => {
+                return self.fail(
+                    request,
+                    Message::ProgramReadError,
+                    &[program, &err.to_string()],
+                );
+            }
+        };
+
+        let args = arguments["args"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|arg| arg.as_str().map(String::from))
+            .collect();
+
+        self.stepper.mode = if arguments["stopOnEntry"].as_bool().unwrap_or(false) {
+            Mode::Step
+        } else {
+            Mode::Continue
+        };
+        self.launch = Some(Launch {
+            program: program.to_string(),
+            source,
+            args,
+        });
+
+        self.respond(request, json!({}))
+    }
+
+    /// Replaces the breakpoints. The script is a single file, so the source
+    /// they are set in is not checked.
+    fn set_breakpoints(&mut self, request: &Value) -> io::Result<()> {
+        let lines: Vec<usize> = request["arguments"]["breakpoints"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|breakpoint| breakpoint["line"].as_u64())
+            .map(|line| line as usize)
+            .collect();
+
+        self.stepper.breakpoints = lines.iter().copied().collect();
+
+        let breakpoints: Vec<Value> = lines
+            .iter()
+            .map(|line| json!({ "verified": true, "line": line }))
+            .collect();
+        self.respond(request, json!({ "breakpoints": breakpoints }))
+    }
+
+    fn threads(&mut self, request: &Value) -> io::Result<()> {
+        let threads = json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] });
+        self.respond(request, threads)
+    }
+
+    fn stack_trace(&mut self, request: &Value, interpreter: &Interpreter) -> io::Result<()> {
+        let program = self.launch.as_ref().map(|launch| launch.program.clone());
+
+        let frames: Vec<Value> = interpreter
+            .frames()
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(id, frame)| {
+                json!({
+                    "id": id,
+                    "name": frame.expression,
+                    "line": frame.line,
+                    "column": frame.column,
+                    "source": { "path": program },
+                })
+            })
+            .collect();
+
+        let body = json!({ "totalFrames": frames.len(), "stackFrames": frames });
+        self.respond(request, body)
+    }
+
+    fn variables(&mut self, request: &Value, interpreter: &Interpreter) -> io::Result<()> {
+        let values = match request["arguments"]["variablesReference"].as_u64() {
+            Some(LOCALS_REFERENCE) => interpreter.locals(),
+            Some(GLOBALS_REFERENCE) => interpreter
+                .global_names()
+                .into_iter()
+                .filter_map(|name| {
+                    let value = interpreter.get_global(&name)?;
+                    Some((name, value))
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        let variables: Vec<Value> = values
+            .into_iter()
+            .map(|(name, value)| {
+                json!({
+                    "name": name,
+                    "value": interpreter.stringify(value),
+                    "variablesReference": 0,
+                })
+            })
+            .collect();
+
+        self.respond(request, json!({ "variables": variables }))
+    }
+
+    /// Assigns a variable the value of the Lox expression the client sent,
+    /// in the scope it's listed in.
+    fn set_variable(&mut self, request: &Value, interpreter: &mut Interpreter) -> io::Result<()> {
+        let arguments = &request["arguments"];
+        let name = arguments["name"].as_str().unwrap_or_default();
+        let value = arguments["value"].as_str().unwrap_or_default();
+
+        if !debugger::is_identifier(name) {
+            return self.fail(request, Message::NotAVariableName, &[name]);
+        }
+
+        match debugger::evaluate(interpreter, value) {
+            Ok(value) => {
+                if arguments["variablesReference"] == LOCALS_REFERENCE {
+                    interpreter.assign(name, value.clone());
+                } else {
+                    interpreter.define_global(name, value.clone());
+                }
+                let value = interpreter.stringify(value);
+                self.respond(request, json!({ "value": value }))
+            }
+            Err(diagnostics) => {
+                let locale = interpreter.locale();
+                let message: Vec<String> = diagnostics
+                    .iter()
+                    .map(|diagnostic| diagnostic.render(locale))
+                    .collect();
+                self.send_failure(request, &message.join("\n"))
+            }
+        }
+    }
+
+    fn read_request(&mut self) -> io::Result<Option<Value>> {
+        let Some(body) = read_message(&mut self.input)? else {
+            return Ok(None);
+        };
+
+        serde_json::from_slice(&body)
+            .map(Some)
+            .map_err(io::Error::from)
+    }
+
+    fn respond(&mut self, request: &Value, body: Value) -> io::Result<()> {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "success": true,
+            "command": request["command"],
+            "body": body,
+        }))
+    }
+
+    fn fail(&mut self, request: &Value, message: Message, args: &[&str]) -> io::Result<()> {
+        let text = self.locale.text(message, args);
+        self.send_failure(request, &text)
+    }
+
+    fn send_failure(&mut self, request: &Value, message: &str) -> io::Result<()> {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "success": false,
+            "command": request["command"],
+            "message": message,
+        }))
+    }
+
+    fn event(&mut self, event: &str, body: Value) -> io::Result<()> {
+        self.send(json!({ "type": "event", "event": event, "body": body }))
+    }
+
+    fn send(&mut self, mut message: Value) -> io::Result<()> {
+        self.seq += 1;
+        message["seq"] = json!(self.seq);
+
+        protocol::write_message(&mut self.output, &message)
+    }
+}
+
+struct Adapter {
+    session: Rc<RefCell<Session>>,
+}
+
+impl Hook for Adapter {
+    fn pause(&mut self, interpreter: &mut Interpreter) -> bool {
+        let mut session = self.session.borrow_mut();
+        let Some(reason) = session.stepper.stop(interpreter.frames()) else {
+            return true;
+        };
+
+        session.pause(interpreter, reason).unwrap_or_else(|_| {
+            session.disconnected = true;
+            false
+        })
+    }
+}
+
+/// Forwards what the script writes to the client as output events, one per
+/// line.
+struct Output {
+    session: Rc<RefCell<Session>>,
+    category: &'static str,
+    line: Vec<u8>,
+    /// Text written while the session was busy, sent with the next text.
+    pending: Vec<u8>,
+}
+
+impl Output {
+    fn new(session: &Rc<RefCell<Session>>, category: &'static str) -> Self {
+        Self {
+            session: Rc::clone(session),
+            category,
+            line: Vec::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    fn send(&mut self, text: Vec<u8>) -> io::Result<()> {
+        self.pending.extend(text);
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        // A paused script's session evaluates what the client sends, and
+        // that code can write or exit while the session is borrowed.
+        let Ok(mut session) = self.session.try_borrow_mut() else {
+            return Ok(());
+        };
+
+        let text = std::mem::take(&mut self.pending);
+        if session.disconnected {
+            return Ok(());
+        }
+
+        let output = String::from_utf8_lossy(&text);
+        session.event(
+            "output",
+            json!({ "category": self.category, "output": output }),
+        )
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.line.extend_from_slice(buf);
+
+        while let Some(end) = self.line.iter().position(|&byte| byte == b'\n') {
+            let line = self.line.drain(..=end).collect();
+            self.send(line)?;
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let line = std::mem::take(&mut self.line);
+        self.send(line)
+    }
+}
@@ -0,0 +1,304 @@
+use std::{
+    collections::BTreeSet,
+    io::{BufRead, Write},
+};
+
+use crate::{
+    error_handler::{Diagnostic, ErrorHandler},
+    expr::Expr,
+    interpreter::Interpreter,
+    lint::Lints,
+    lox::Lox,
+    messages::{Locale, Message},
+    token::{LiteralType, Token},
+};
+
+/// An expression being evaluated while a debug hook is installed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub line: usize,
+    pub column: usize,
+    /// The expression, parenthesized like `--ast` prints it.
+    pub expression: String,
+}
+
+/// Called by the interpreter before each expression it can stop at, which is
+/// any expression with a token. Normal runs have no hook and skip the call.
+pub trait Hook {
+    /// Returns `false` to abort the script.
+    fn pause(&mut self, interpreter: &mut Interpreter) -> bool;
+}
+
+/// The token that locates `expr` in the source, if it has one.
+pub fn stop_token(expr: &Expr) -> Option<&Token> {
+    match expr {
+        Expr::Binary(binary) => Some(&binary.operator),
+        Expr::Unary(unary) => Some(&unary.operator),
+        Expr::Assign(assign) => Some(&assign.operator),
+        Expr::Increment(increment) => Some(&increment.operator),
+        Expr::Call(call) => Some(&call.paren),
+        Expr::Variable(variable) => Some(&variable.name),
+        Expr::Grouping(_)
+        | Expr::Literal(_)
+        | Expr::Comma(_)
+        | Expr::Ternary(_)
+        | Expr::Lambda(_) => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Step,
+    /// Stop at an expression no deeper than this.
+    Next(usize),
+    /// Stop at an expression shallower than this.
+    Out(usize),
+    Continue,
+    /// The client is gone, so the script runs to the end.
+    Detached,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StopReason {
+    Step,
+    Breakpoint,
+}
+
+/// Decides where execution stops. Breakpoints are by line, so a breakpoint
+/// only stops the first expression evaluated on its line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stepper {
+    pub breakpoints: BTreeSet<usize>,
+    pub mode: Mode,
+    last_line: Option<usize>,
+}
+
+impl Stepper {
+    pub fn new(mode: Mode) -> Self {
+        Self {
+            breakpoints: BTreeSet::new(),
+            mode,
+            last_line: None,
+        }
+    }
+
+    /// Whether to stop before the innermost of `frames`.
+    pub fn stop(&mut self, frames: &[Frame]) -> Option<StopReason> {
+        let frame = frames.last()?;
+        let at_breakpoint =
+            self.breakpoints.contains(&frame.line) && self.last_line != Some(frame.line);
+        self.last_line = Some(frame.line);
+
+        let step = match self.mode {
+            Mode::Step => true,
+            Mode::Next(depth) => frames.len() <= depth,
+            Mode::Out(depth) => frames.len() < depth,
+            Mode::Continue => false,
+            Mode::Detached => return None,
+        };
+
+        if at_breakpoint {
+            Some(StopReason::Breakpoint)
+        } else if step {
+            Some(StopReason::Step)
+        } else {
+            None
+        }
+    }
+}
+
+/// Evaluates `source` in the scope the script is paused in, without the
+/// hook.
+pub fn evaluate(
+    interpreter: &mut Interpreter,
+    source: &str,
+) -> Result<LiteralType, Vec<Diagnostic>> {
+    let mut error_handler = ErrorHandler::new(interpreter.locale(), Lints::default());
+
+    let Some(expr) = Lox::parse(source, &mut error_handler) else {
+        return Err(error_handler.into_diagnostics());
+    };
+
+    interpreter.evaluate(expr).map_err(|err| {
+        error_handler.runtime_error(err);
+        error_handler.into_diagnostics()
+    })
+}
+
+pub fn is_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Breaks on `debug` console commands.
+///
+/// Lox has no statements, so the debugger steps through expressions, the
+/// backtrace lists the expressions enclosing the current one, and every
+/// frame sees the scope of the innermost one.
+pub struct Debugger<R, W> {
+    source: String,
+    input: R,
+    output: W,
+    stepper: Stepper,
+    locale: Locale,
+}
+
+impl<R: BufRead, W: Write> Debugger<R, W> {
+    pub fn new(source: &str, input: R, output: W, locale: Locale) -> Self {
+        Self {
+            source: source.to_string(),
+            input,
+            output,
+            stepper: Stepper::new(Mode::Step),
+            locale,
+        }
+    }
+
+    /// Reads commands until one resumes the script.
+    fn console(&mut self, interpreter: &mut Interpreter) -> bool {
+        let frames = interpreter.frames().to_vec();
+        let frame = frames.last().expect("paused outside an expression");
+        self.say(&format!("[line {}] {}", frame.line, frame.expression));
+
+        loop {
+            self.write("(rlox) ");
+
+            let mut line = String::new();
+            if self.input.read_line(&mut line).unwrap_or(0) == 0 {
+                self.stepper.mode = Mode::Detached;
+                return true;
+            }
+
+            let (command, argument) = match line.trim().split_once(' ') {
+                Some((command, argument)) => (command, argument.trim()),
+                None => (line.trim(), ""),
+            };
+
+            match command {
+                "" => {}
+                "b" | "break" => self.set_breakpoint(argument, true),
+                "d" | "delete" => self.set_breakpoint(argument, false),
+                "breakpoints" => {
+                    let lines: Vec<String> = self
+                        .stepper
+                        .breakpoints
+                        .iter()
+                        .map(usize::to_string)
+                        .collect();
+                    self.tell(Message::BreakpointList, &[&lines.join(", ")]);
+                }
+                "s" | "step" => return self.resume(Mode::Step),
+                "n" | "next" => return self.resume(Mode::Next(frames.len())),
+                "o" | "out" => return self.resume(Mode::Out(frames.len())),
+                "c" | "continue" => return self.resume(Mode::Continue),
+                "bt" | "backtrace" => {
+                    for (index, frame) in frames.iter().rev().enumerate() {
+                        self.say(&format!(
+                            "#{} [line {}] {}",
+                            index, frame.line, frame.expression
+                        ));
+                    }
+                }
+                "l" | "list" => self.list(frame.line),
+                "p" | "print" => {
+                    if let Some(value) = self.evaluate(interpreter, argument) {
+                        let text = interpreter.stringify(value);
+                        self.say(&text);
+                    }
+                }
+                "set" => match argument.split_once('=') {
+                    Some((name, expression)) if is_identifier(name.trim()) => {
+                        if let Some(value) = self.evaluate(interpreter, expression) {
+                            interpreter.assign(name.trim(), value);
+                        }
+                    }
+                    _ => self.tell(Message::SetUsage, &[]),
+                },
+                "q" | "quit" => return false,
+                "h" | "help" => {
+                    let help = self.locale.text(Message::DebuggerHelp, &[]);
+                    self.write(&help);
+                }
+                _ => self.tell(Message::UnknownDebuggerCommand, &[command]),
+            }
+        }
+    }
+
+    fn resume(&mut self, mode: Mode) -> bool {
+        self.stepper.mode = mode;
+        true
+    }
+
+    fn set_breakpoint(&mut self, argument: &str, enabled: bool) {
+        let Ok(line) = argument.parse::<usize>() else {
+            self.tell(Message::ExpectLineNumber, &[]);
+            return;
+        };
+
+        if enabled {
+            self.stepper.breakpoints.insert(line);
+            self.tell(Message::BreakpointSet, &[&line.to_string()]);
+        } else if self.stepper.breakpoints.remove(&line) {
+            self.tell(Message::BreakpointRemoved, &[&line.to_string()]);
+        } else {
+            self.tell(Message::NoBreakpoint, &[&line.to_string()]);
+        }
+    }
+
+    fn list(&mut self, current: usize) {
+        let first = current.saturating_sub(3).max(1);
+        let lines: Vec<String> = self
+            .source
+            .lines()
+            .enumerate()
+            .map(|(index, text)| (index + 1, text))
+            .skip(first - 1)
+            .take(7)
+            .map(|(number, text)| {
+                let marker = if number == current { "=>" } else { "  " };
+                format!("{} {:>4} | {}", marker, number, text)
+            })
+            .collect();
+
+        for line in lines {
+            self.say(&line);
+        }
+    }
+
+    fn evaluate(&mut self, interpreter: &mut Interpreter, source: &str) -> Option<LiteralType> {
+        match evaluate(interpreter, source) {
+            Ok(value) => Some(value),
+            Err(diagnostics) => {
+                for diagnostic in diagnostics {
+                    self.say(&diagnostic.render(interpreter.locale()));
+                }
+                None
+            }
+        }
+    }
+
+    fn tell(&mut self, message: Message, args: &[&str]) {
+        let text = self.locale.text(message, args);
+        self.say(&text);
+    }
+
+    fn say(&mut self, text: &str) {
+        self.write(&format!("{}\n", text));
+    }
+
+    fn write(&mut self, text: &str) {
+        self.output
+            .write_all(text.as_bytes())
+            .and_then(|_| self.output.flush())
+            .expect("Error writing to the debug console");
+    }
+}
+
+impl<R: BufRead, W: Write> Hook for Debugger<R, W> {
+    fn pause(&mut self, interpreter: &mut Interpreter) -> bool {
+        match self.stepper.stop(interpreter.frames()) {
+            Some(_) => self.console(interpreter),
+            None => true,
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+};
+
+use crate::token::LiteralType;
+
+/// The parameters of a call, and through `enclosing` those of the calls its
+/// function was made in. Globals live in the interpreter instead.
+#[derive(Debug, Default)]
+pub struct Environment {
+    values: HashMap<String, LiteralType>,
+    enclosing: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Environment {
+    pub fn new(enclosing: Option<Rc<RefCell<Environment>>>) -> Self {
+        Self {
+            values: HashMap::new(),
+            enclosing,
+        }
+    }
+
+    pub fn define(&mut self, name: &str, value: LiteralType) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    /// Replaces the value of `name` where it's defined, returning whether
+    /// any environment in the chain defines it.
+    pub fn assign(&mut self, name: &str, value: &LiteralType) -> bool {
+        match self.values.get_mut(name) {
+            Some(slot) => {
+                *slot = value.clone();
+                true
+            }
+            None => self
+                .enclosing
+                .as_ref()
+                .is_some_and(|enclosing| enclosing.borrow_mut().assign(name, value)),
+        }
+    }
+
+    /// Looks `name` up here, then in the enclosing environments.
+    pub fn get(&self, name: &str) -> Option<LiteralType> {
+        match self.values.get(name) {
+            Some(value) => Some(value.clone()),
+            None => self.enclosing.as_ref()?.borrow().get(name),
+        }
+    }
+
+    /// Every name in reach, with the value it has here, sorted by name.
+    /// Inner definitions hide the outer ones.
+    pub fn visible(&self) -> BTreeMap<String, LiteralType> {
+        let mut values = match &self.enclosing {
+            Some(enclosing) => enclosing.borrow().visible(),
+            None => BTreeMap::new(),
+        };
+        values.extend(self.values.clone());
+
+        values
+    }
+}
@@ -1,51 +1,409 @@
-use crate::{token::Token, token_type::TokenType};
+use std::fmt::{self, Display};
+
+use crate::{
+    compat::Compat,
+    lint::{self, Level, Lint, Lints},
+    messages::{Locale, Message},
+    token::Token,
+    token_type::TokenType,
+};
 
 #[derive(Debug)]
 pub struct RuntimeError {
-    token: Token,
+    line: usize,
     message: String,
+    key: Option<Message>,
+    stack: Vec<StackFrame>,
+    /// Line being executed in the frame the error is leaving next.
+    frame_line: usize,
+    /// Set when the script called `exit()`, which unwinds like an error.
+    exit: Option<i32>,
 }
 
 impl RuntimeError {
     pub fn new(token: Token, message: &str) -> Self {
+        Self::at_line(token.line, message)
+    }
+
+    /// An error that has no token to point at, only the line it was on.
+    pub fn at_line(line: usize, message: &str) -> Self {
         Self {
-            token,
+            line,
             message: message.to_string(),
+            key: None,
+            stack: Vec::new(),
+            frame_line: line,
+            exit: None,
         }
     }
+
+    /// Unwinds the script because it called `exit(code)`.
+    pub fn exit(line: usize, code: i32) -> Self {
+        Self {
+            exit: Some(code),
+            ..Self::at_line(line, "")
+        }
+    }
+
+    /// Records that the error unwound out of `function`, which was called
+    /// on `call_line` and written in `file`, if it has one.
+    pub fn leave_function(mut self, function: &str, file: Option<&str>, call_line: usize) -> Self {
+        self.stack.push(StackFrame {
+            function: Some(function.to_string()),
+            file: file.map(String::from),
+            line: self.frame_line,
+        });
+        self.frame_line = call_line;
+        self
+    }
+
+    /// Records that the error unwound out of the script, read from `file`
+    /// when it came from one. This is the outermost frame.
+    pub fn leave_script(mut self, file: Option<&str>) -> Self {
+        self.stack.push(StackFrame {
+            function: None,
+            file: file.map(String::from),
+            line: self.frame_line,
+        });
+        self
+    }
+
+    /// Tags the error with the catalog message it was built from, which
+    /// gives it a code.
+    pub fn with_key(mut self, key: Message) -> Self {
+        self.key = Some(key);
+        self
+    }
+}
+
+/// A call active when a runtime error happened.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackFrame {
+    /// `None` for the top level of the script.
+    pub function: Option<String>,
+    /// The script's path. Natives have none, and neither do scripts run
+    /// from a string.
+    pub file: Option<String>,
+    pub line: usize,
+}
+
+impl StackFrame {
+    pub fn render(&self, locale: Locale) -> String {
+        let function = match &self.function {
+            Some(function) => locale.text(Message::InFunction, &[function]),
+            None => locale.text(Message::InScript, &[]),
+        };
+
+        format!("[line {}] {}", self.line, function)
+    }
+}
+
+impl Display for StackFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render(Locale::default()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiagnosticKind {
+    Syntax,
+    Runtime,
+    Warning,
+    /// The script called `exit()` with this status. It renders as nothing.
+    Exit(i32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Location {
+    Unknown,
+    End,
+    Token(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub line: usize,
+    /// Byte column of the token the diagnostic points at, when there is one.
+    pub column: Option<usize>,
+    pub location: Location,
+    pub message: String,
+    /// Stable code such as `L0102`, explained by `--explain`.
+    pub code: Option<&'static str>,
+    /// Innermost call first. Only runtime errors have one.
+    pub stack: Vec<StackFrame>,
+}
+
+impl Diagnostic {
+    /// Renders the diagnostic in `locale`'s language. jlox output carries no
+    /// codes and is always English, so it stays byte-for-byte comparable with
+    /// the reference implementation.
+    pub fn render(&self, locale: Locale) -> String {
+        match (self.kind, locale.compat) {
+            (DiagnosticKind::Exit(_), _) => String::new(),
+            (DiagnosticKind::Syntax | DiagnosticKind::Warning, Compat::Rlox) => {
+                let location = match &self.location {
+                    Location::Unknown => String::new(),
+                    Location::End => locale.text(Message::AtEnd, &[]),
+                    Location::Token(lexeme) => {
+                        format!(" {}", locale.text(Message::AtToken, &[lexeme]))
+                    }
+                };
+                let severity = match self.code {
+                    Some(code) => format!("{}[{}]", self.severity(locale), code),
+                    None => self.severity(locale),
+                };
+
+                format!(
+                    "[line: {}] {} {}: {}",
+                    self.line, severity, location, self.message
+                )
+            }
+            (DiagnosticKind::Syntax | DiagnosticKind::Warning, Compat::Jlox) => {
+                let location = match &self.location {
+                    Location::Unknown => String::new(),
+                    Location::End => String::from(" at end"),
+                    Location::Token(lexeme) => format!(" at '{}'", lexeme),
+                };
+
+                format!(
+                    "[line {}] {}{}: {}",
+                    self.line,
+                    self.severity(locale),
+                    location,
+                    self.message
+                )
+            }
+            (DiagnosticKind::Runtime, Compat::Rlox) => {
+                let message = match self.code {
+                    Some(code) => format!(
+                        "{}[{}]: {} ",
+                        locale.text(Message::ErrorSeverity, &[]),
+                        code,
+                        self.message
+                    ),
+                    None => format!("{} ", self.message),
+                };
+                let trace = if self.stack.is_empty() {
+                    format!("[line {}]", self.line)
+                } else {
+                    let frames: Vec<String> = self
+                        .stack
+                        .iter()
+                        .map(|frame| frame.render(locale))
+                        .collect();
+                    frames.join("\n")
+                };
+
+                format!("{}\n{}", message, trace)
+            }
+            (DiagnosticKind::Runtime, Compat::Jlox) => {
+                format!("{}\n[line {}]", self.message, self.line)
+            }
+        }
+    }
+
+    fn severity(&self, locale: Locale) -> String {
+        let severity = match self.kind {
+            DiagnosticKind::Syntax | DiagnosticKind::Runtime | DiagnosticKind::Exit(_) => {
+                Message::ErrorSeverity
+            }
+            DiagnosticKind::Warning => Message::WarningSeverity,
+        };
+
+        locale.text(severity, &[])
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render(Locale::default()))
+    }
 }
 
 pub struct ErrorHandler {
-    pub had_error: bool,
-    pub had_runtime_error: bool,
+    pub locale: Locale,
+    lints: Lints,
+    diagnostics: Vec<Diagnostic>,
 }
 
 impl ErrorHandler {
-    pub fn new() -> Self {
+    pub fn new(locale: Locale, lints: Lints) -> Self {
         Self {
-            had_error: false,
-            had_runtime_error: false,
+            locale,
+            lints,
+            diagnostics: Vec::new(),
         }
     }
 
-    pub fn error(&mut self, line: usize, message: &str) {
-        self.report(line, "", message);
+    pub fn error(&mut self, line: usize, message: Message, args: &[&str]) {
+        self.report(line, None, Location::Unknown, message, args);
     }
 
-    pub fn report(&mut self, line: usize, _where: &str, message: &str) {
-        eprintln!("[line: {}] Error {}: {}", line, _where, message);
-        self.had_error = true;
+    /// Reports a syntax error at `token` and returns the localized message.
+    pub fn error_with_token(&mut self, token: &Token, message: Message, args: &[&str]) -> String {
+        let location = if token.token_type == TokenType::EOF {
+            Location::End
+        } else {
+            Location::Token(token.lexeme.clone())
+        };
+
+        self.report(token.line, Some(token.column), location, message, args)
     }
 
-    pub fn error_with_token(&mut self, token: &Token, message: &str) {
-        if token.token_type == TokenType::EOF {
-            self.report(token.line, "at end", message);
-        } else {
-            self.report(token.line, &format!(" at'{}'", token.lexeme), message)
+    /// Reports `lint` at `token`, at the level the lint is set to.
+    pub fn warning(&mut self, lint: Lint, token: &Token, message: Message, args: &[&str]) {
+        let location = Location::Token(token.lexeme.clone());
+        self.lint(
+            lint,
+            token.line,
+            Some(token.column),
+            location,
+            message,
+            args,
+        );
+    }
+
+    /// Applies a `// lox: level(lints)` comment to the whole source.
+    pub fn pragma(&mut self, line: usize, comment: &str) {
+        let Some((level, names)) = lint::parse_pragma(comment) else {
+            return;
+        };
+
+        for name in names {
+            match Lint::from_name(name) {
+                Some(lint) => self.lints.set_level(lint, level),
+                None => self.lint(
+                    Lint::UnknownLints,
+                    line,
+                    None,
+                    Location::Unknown,
+                    Message::UnknownLint,
+                    &[name],
+                ),
+            }
         }
     }
 
+    fn lint(
+        &mut self,
+        lint: Lint,
+        line: usize,
+        column: Option<usize>,
+        location: Location,
+        message: Message,
+        args: &[&str],
+    ) {
+        let kind = match (self.lints.level(lint), self.locale.compat) {
+            // jlox has no warnings, so they would only break conformance.
+            (Level::Allow, _) | (Level::Warn, Compat::Jlox) => return,
+            (Level::Warn, Compat::Rlox) => DiagnosticKind::Warning,
+            (Level::Deny, _) => DiagnosticKind::Syntax,
+        };
+
+        self.push(kind, line, column, location, message, args);
+    }
+
+    fn report(
+        &mut self,
+        line: usize,
+        column: Option<usize>,
+        location: Location,
+        message: Message,
+        args: &[&str],
+    ) -> String {
+        self.push(
+            DiagnosticKind::Syntax,
+            line,
+            column,
+            location,
+            message,
+            args,
+        )
+    }
+
+    fn push(
+        &mut self,
+        kind: DiagnosticKind,
+        line: usize,
+        column: Option<usize>,
+        location: Location,
+        message: Message,
+        args: &[&str],
+    ) -> String {
+        let text = self.locale.text(message, args);
+
+        self.diagnostics.push(Diagnostic {
+            kind,
+            line,
+            column,
+            location,
+            message: text.clone(),
+            code: message.code(),
+            stack: Vec::new(),
+        });
+
+        text
+    }
+
     pub fn runtime_error(&mut self, error: RuntimeError) {
-        println!("{} \n[line {}]", error.message, error.token.line);
+        if let Some(code) = error.exit {
+            self.diagnostics.push(Diagnostic {
+                kind: DiagnosticKind::Exit(code),
+                line: error.line,
+                column: None,
+                location: Location::Unknown,
+                message: String::new(),
+                code: None,
+                stack: Vec::new(),
+            });
+            return;
+        }
+
+        self.diagnostics.push(Diagnostic {
+            kind: DiagnosticKind::Runtime,
+            line: error.line,
+            column: None,
+            location: Location::Unknown,
+            message: error.message,
+            code: error.key.and_then(Message::code),
+            stack: error.stack,
+        });
+    }
+
+    pub fn had_error(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.kind == DiagnosticKind::Syntax)
+    }
+
+    pub fn into_diagnostics(self) -> Vec<Diagnostic> {
+        self.diagnostics
+    }
+}
+
+/// The exit status of a script that reported `diagnostics`, following the
+/// BSD `sysexits` codes.
+pub fn exit_code(diagnostics: &[Diagnostic]) -> i32 {
+    let had_error = |kind| diagnostics.iter().any(|d: &Diagnostic| d.kind == kind);
+    let interrupted = diagnostics
+        .iter()
+        .any(|d| d.code == Message::Interrupted.code());
+
+    let exit = diagnostics.iter().find_map(|d| match d.kind {
+        DiagnosticKind::Exit(code) => Some(code),
+        _ => None,
+    });
+
+    if let Some(code) = exit {
+        code
+    } else if interrupted {
+        130
+    } else if had_error(DiagnosticKind::Syntax) {
+        65
+    } else if had_error(DiagnosticKind::Runtime) {
+        70
+    } else {
+        0
     }
 }
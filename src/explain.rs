@@ -0,0 +1,373 @@
+use crate::{
+    compat::Compat,
+    messages::{Language, Locale, Message},
+};
+
+/// Example of a coded message. The description lives in the message catalog,
+/// and `args` name the placeholders of the message's template.
+struct Explanation {
+    message: Message,
+    args: &'static [&'static str],
+    wrong: &'static str,
+    right: &'static str,
+}
+
+static EXPLANATIONS: &[Explanation] = &[
+    Explanation {
+        message: Message::UnterminatedString,
+        args: &[],
+        wrong: "\"hello",
+        right: "\"hello\"",
+    },
+    Explanation {
+        message: Message::UnexpectedCharacter,
+        args: &["<character>"],
+        wrong: "1 @ 2",
+        right: "1 + 2",
+    },
+    Explanation {
+        message: Message::UnterminatedComment,
+        args: &[],
+        wrong: "/* outer /* inner */ 1",
+        right: "/* outer /* inner */ */ 1",
+    },
+    Explanation {
+        message: Message::ExpectExpression,
+        args: &[],
+        wrong: "1 + * 2",
+        right: "1 + 2",
+    },
+    Explanation {
+        message: Message::ExpectRightParenAfterExpression,
+        args: &[],
+        wrong: "(1 + 2",
+        right: "(1 + 2)",
+    },
+    Explanation {
+        message: Message::ExpectColonAfterThenBranch,
+        args: &[],
+        wrong: "true ? 1",
+        right: "true ? 1 : 2",
+    },
+    Explanation {
+        message: Message::ExpectRightParenAfterArguments,
+        args: &[],
+        wrong: "env(\"HOME\"",
+        right: "env(\"HOME\")",
+    },
+    Explanation {
+        message: Message::TooManyArguments,
+        args: &[],
+        wrong: "f(a1, a2, ..., a256)",
+        right: "f(a1, a2, ..., a255)",
+    },
+    Explanation {
+        message: Message::TooMuchNesting,
+        args: &[],
+        wrong: "((((((((... 1 ...))))))))",
+        right: "1",
+    },
+    Explanation {
+        message: Message::OutsideLoop,
+        args: &["<keyword>"],
+        wrong: "clock() > 0 ? 1 : break",
+        right: "clock() > 0 ? 1 : 0",
+    },
+    Explanation {
+        message: Message::TooManyParameters,
+        args: &[],
+        wrong: "(a1, a2, ..., a256) => a1",
+        right: "(a1, a2, ..., a255) => a1",
+    },
+    Explanation {
+        message: Message::DuplicateParameter,
+        args: &["<name>"],
+        wrong: "(a, a) => a + a",
+        right: "(a, b) => a + b",
+    },
+    Explanation {
+        message: Message::InvalidAssignmentTarget,
+        args: &[],
+        wrong: "f() += 1",
+        right: "count += 1",
+    },
+    Explanation {
+        message: Message::ExpectEndOfExpression,
+        args: &[],
+        wrong: "(1 + 2))",
+        right: "(1 + 2)",
+    },
+    Explanation {
+        message: Message::ExpectLeftParenAfterFun,
+        args: &[],
+        wrong: "fun { return 1; }",
+        right: "fun () { return 1; }",
+    },
+    Explanation {
+        message: Message::ExpectParameterName,
+        args: &[],
+        wrong: "fun (a, 1) { return a + 1; }",
+        right: "fun (a, b) { return a + b; }",
+    },
+    Explanation {
+        message: Message::ExpectRightParenAfterParameters,
+        args: &[],
+        wrong: "fun (a b) { return a + b; }",
+        right: "fun (a, b) { return a + b; }",
+    },
+    Explanation {
+        message: Message::ExpectLeftBraceBeforeBody,
+        args: &[],
+        wrong: "fun (a) return a;",
+        right: "fun (a) { return a; }",
+    },
+    Explanation {
+        message: Message::ExpectRightBraceAfterBody,
+        args: &[],
+        wrong: "fun (a) { return a; a; }",
+        right: "fun (a) { return a; }",
+    },
+    Explanation {
+        message: Message::OperandMustBeNumber,
+        args: &[],
+        wrong: "-\"a\"",
+        right: "-1",
+    },
+    Explanation {
+        message: Message::OperandsMustBeNumbers,
+        args: &[],
+        wrong: "1 < \"2\"",
+        right: "1 < 2",
+    },
+    Explanation {
+        message: Message::OperandsMustBeNumbersOrStrings,
+        args: &[],
+        wrong: "\"a\" + 1",
+        right: "\"a\" + \"1\"",
+    },
+    Explanation {
+        message: Message::UndefinedVariable,
+        args: &["<name>"],
+        wrong: "clok()",
+        right: "clock()",
+    },
+    Explanation {
+        message: Message::NotCallable,
+        args: &[],
+        wrong: "\"clock\"()",
+        right: "clock()",
+    },
+    Explanation {
+        message: Message::WrongArity,
+        args: &["<expected>", "<got>"],
+        wrong: "clock(1)",
+        right: "clock()",
+    },
+    Explanation {
+        message: Message::Interrupted,
+        args: &[],
+        wrong: "clock()  // ^C",
+        right: "clock()",
+    },
+    Explanation {
+        message: Message::EnvironmentNameMustBeString,
+        args: &[],
+        wrong: "env(1)",
+        right: "env(\"HOME\")",
+    },
+    Explanation {
+        message: Message::ExitCodeMustBeInteger,
+        args: &[],
+        wrong: "exit(256)",
+        right: "exit(1)",
+    },
+    Explanation {
+        message: Message::ClockUnavailable,
+        args: &[],
+        wrong: "clock()  // 1969-12-31",
+        right: "clock()  // 2026-10-18",
+    },
+    Explanation {
+        message: Message::StackOverflow,
+        args: &[],
+        wrong: "-(-(-(-1)))  // --max-depth 3",
+        right: "-(-(-(-1)))  // --max-depth 8",
+    },
+    Explanation {
+        message: Message::StepLimitExceeded,
+        args: &[],
+        wrong: "1 + 2 + 3  // --max-steps 3",
+        right: "1 + 2 + 3  // --max-steps 5",
+    },
+    Explanation {
+        message: Message::TimeLimitExceeded,
+        args: &[],
+        wrong: "clock()  // --timeout 0",
+        right: "clock()  // --timeout 1",
+    },
+    Explanation {
+        message: Message::HeapLimitExceeded,
+        args: &[],
+        wrong: "\"ab\" + \"cd\"  // --max-heap 3",
+        right: "\"ab\" + \"cd\"  // --max-heap 4",
+    },
+    Explanation {
+        message: Message::Sandboxed,
+        args: &[],
+        wrong: "env(\"HOME\")  // --sandbox",
+        right: "clock()  // --sandbox",
+    },
+    Explanation {
+        message: Message::OperandMustBeInteger,
+        args: &[],
+        wrong: "~1.5",
+        right: "~1",
+    },
+    Explanation {
+        message: Message::OperandsMustBeIntegers,
+        args: &[],
+        wrong: "5 & 0.5",
+        right: "5 & 1",
+    },
+    Explanation {
+        message: Message::ShiftOutOfRange,
+        args: &[],
+        wrong: "1 << 60",
+        right: "1 << 52",
+    },
+    Explanation {
+        message: Message::ArgumentIndexMustBeInteger,
+        args: &[],
+        wrong: "arg(\"first\")",
+        right: "arg(0)",
+    },
+    Explanation {
+        message: Message::SelfComparison,
+        args: &["<name>"],
+        wrong: "count == count",
+        right: "count == limit",
+    },
+    Explanation {
+        message: Message::UnknownLint,
+        args: &["<lint>"],
+        wrong: "// lox: allow(self_compare)",
+        right: "// lox: allow(self_comparison)",
+    },
+    Explanation {
+        message: Message::UnusedParameter,
+        args: &["<name>"],
+        wrong: "(a, b) => a * 2",
+        right: "(a) => a * 2",
+    },
+    Explanation {
+        message: Message::ShadowedParameter,
+        args: &["<name>"],
+        wrong: "(x) => (x) => x + 1",
+        right: "(x) => (y) => x + y",
+    },
+];
+
+/// Describes an error code such as `L0102` with an example of the mistake and
+/// its fix, or returns the localized reason the code is unknown.
+pub fn explain(code: &str, language: Language) -> Result<String, String> {
+    let locale = Locale::new(language, Compat::Rlox);
+    let code = code.to_uppercase();
+
+    let explanation = Message::from_code(&code).and_then(|message| {
+        EXPLANATIONS
+            .iter()
+            .find(|explanation| explanation.message == message)
+    });
+
+    match explanation {
+        Some(explanation) => Ok(format!(
+            "{}: {}\n\n{}\n\n{}\n\n    {}\n\n{}\n\n    {}\n",
+            code,
+            locale.text(explanation.message, explanation.args),
+            locale.description(explanation.message),
+            locale.text(Message::WrongExample, &[]),
+            explanation.wrong,
+            locale.text(Message::RightExample, &[]),
+            explanation.right
+        )),
+        None => Err(locale.text(Message::UnknownErrorCode, &[&code])),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Lox;
+
+    #[test]
+    fn test_every_code_is_explained() {
+        for message in Message::ALL {
+            if let Some(code) = message.code() {
+                assert!(
+                    explain(code, Language::En).is_ok(),
+                    "{} is not explained",
+                    code
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_explain() {
+        let text = explain("l0102", Language::En).unwrap();
+
+        assert!(text.starts_with("L0102: Expect ')' after expression.\n"));
+        assert!(text.contains("    (1 + 2)\n"));
+        assert_eq!(
+            explain("L9999", Language::En),
+            Err(String::from("No explanation for error code 'L9999'."))
+        );
+    }
+
+    #[test]
+    fn test_explain_fills_placeholders() {
+        let text = explain("L0306", Language::En).unwrap();
+
+        assert!(text.starts_with("L0306: Expected <expected> arguments but got <got>.\n"));
+
+        for message in Message::ALL {
+            if let Some(code) = message.code() {
+                let text = explain(code, Language::En).unwrap();
+
+                assert!(!text.contains("{0}"), "{} leaves a placeholder", code);
+            }
+        }
+    }
+
+    #[test]
+    fn test_syntax_examples_parse() {
+        let lox = Lox::new();
+
+        for explanation in EXPLANATIONS {
+            let code = explanation.message.code().unwrap();
+            if !code.starts_with("L01") || explanation.wrong.contains("...") {
+                continue;
+            }
+
+            let diagnostics = lox.check(explanation.wrong);
+            assert!(
+                diagnostics
+                    .iter()
+                    .any(|diagnostic| diagnostic.code == Some(code)),
+                "{} example doesn't report it",
+                code
+            );
+            assert_eq!(lox.check(explanation.right), [], "{} fix", code);
+        }
+    }
+
+    #[test]
+    fn test_explain_in_portuguese() {
+        let text = explain("L0304", Language::PtBr).unwrap();
+
+        assert!(text.starts_with("L0304: Variável '<name>' não definida.\n"));
+        assert!(text.contains("O nome não está definido como global"));
+        assert!(text.contains("Errado:\n\n    clok()\n"));
+        assert!(text.contains("Certo:\n\n    clock()\n"));
+    }
+}
@@ -1,51 +1,89 @@
 use crate::token::{LiteralType, Token};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Binary {
     pub left: Box<Expr>,
     pub operator: Token,
     pub right: Box<Expr>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Grouping {
     pub expression: Box<Expr>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Literal {
     pub value: LiteralType,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Unary {
     pub operator: Token,
     pub right: Box<Expr>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Comma {
     pub left: Box<Expr>,
     pub right: Box<Expr>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Ternary {
     pub condition: Box<Expr>,
     pub then_branch: Box<Expr>,
     pub else_branch: Box<Expr>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+pub struct Call {
+    pub callee: Box<Expr>,
+    pub paren: Token,
+    pub arguments: Vec<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Variable {
+    pub name: Token,
+}
+
+#[derive(Debug, Clone)]
+pub struct Lambda {
+    pub params: Vec<Token>,
+    pub arrow: Token,
+    pub body: Box<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Assign {
+    pub name: Token,
+    pub operator: Token,
+    pub value: Box<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Increment {
+    pub name: Token,
+    pub operator: Token,
+    pub prefix: bool,
+}
+
+#[derive(Debug, Clone)]
 pub enum Expr {
-    Binary(Binary),
-    Grouping(Grouping),
-    Literal(Literal),
-    Unary(Unary),
-    Comma(Comma),
-    Ternary(Ternary),
+    Binary(Box<Binary>),
+    Grouping(Box<Grouping>),
+    Literal(Box<Literal>),
+    Unary(Box<Unary>),
+    Comma(Box<Comma>),
+    Ternary(Box<Ternary>),
+    Call(Box<Call>),
+    Variable(Box<Variable>),
+    Lambda(Box<Lambda>),
+    Assign(Box<Assign>),
+    Increment(Box<Increment>),
 }
 
 pub trait Visitor<T> {
-    fn visit(expr: Expr) -> T;
+    fn visit(&mut self, expr: Expr) -> T;
 }
@@ -0,0 +1,430 @@
+use crate::{
+    error_handler::ErrorHandler,
+    parser::Parser,
+    scanner::Scanner,
+    token::{Token, Trivia, TriviaKind},
+    token_type::TokenType,
+};
+
+const MAX_WIDTH: usize = 80;
+const INDENT: &str = "    ";
+
+/// Reprints `source` in the canonical layout, or returns `None` when it
+/// doesn't parse. Only the spacing between tokens changes: comments are kept
+/// where they were, and the lexemes are copied verbatim.
+pub fn format(source: &str, error_handler: &mut ErrorHandler) -> Option<String> {
+    let tokens = Scanner::lossless(source.to_string(), error_handler)
+        .scan_tokens()
+        .to_owned();
+
+    Parser::new(tokens.clone(), error_handler).parse();
+
+    if error_handler.had_error() {
+        return None;
+    }
+
+    let token_count = tokens.len() - 1;
+    let nodes = Node::tree(tokens);
+
+    let mut formatter = Formatter::new(token_count);
+    formatter.write_nodes(&nodes, 0);
+
+    let mut output = String::new();
+    if source.starts_with("#!") {
+        output.push_str(source.lines().next().unwrap_or_default());
+        output.push('\n');
+    }
+
+    for line in formatter.finish() {
+        output.push_str(line.trim_end());
+        output.push('\n');
+    }
+
+    Some(output)
+}
+
+/// A comment from the trivia, with the lines it spans.
+#[derive(Debug, Clone)]
+struct Comment {
+    text: String,
+    line: usize,
+    end_line: usize,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Token(Token),
+    Comment {
+        comment: Comment,
+        /// Whether the next token starts on a later line.
+        ends_line: bool,
+    },
+    Group {
+        open: Token,
+        children: Vec<Node>,
+        close: Token,
+    },
+}
+
+impl Node {
+    /// Interleaves the comments in the trivia with the tokens and nests
+    /// everything between matching parentheses, which the parser has already
+    /// checked.
+    fn tree(tokens: Vec<Token>) -> Vec<Node> {
+        let mut stack: Vec<(Token, Vec<Node>)> = Vec::new();
+        let mut nodes = Vec::new();
+        let mut comments = Vec::new();
+        let mut line = 1;
+
+        for mut token in tokens {
+            for trivia in std::mem::take(&mut token.leading_trivia) {
+                Node::collect_comment(trivia, &mut line, &mut comments);
+            }
+
+            for comment in comments.drain(..) {
+                let ends_line = comment.text.starts_with("//") || line > comment.end_line;
+                nodes.push(Node::Comment { comment, ends_line });
+            }
+
+            line += token.lexeme.matches('\n').count();
+            for trivia in std::mem::take(&mut token.trailing_trivia) {
+                Node::collect_comment(trivia, &mut line, &mut comments);
+            }
+
+            match token.token_type {
+                TokenType::EOF => {}
+                TokenType::LeftParen => stack.push((token, std::mem::take(&mut nodes))),
+                TokenType::RightParen => {
+                    let (open, outer) = stack.pop().expect("unbalanced parentheses");
+                    let children = std::mem::replace(&mut nodes, outer);
+                    nodes.push(Node::Group {
+                        open,
+                        children,
+                        close: token,
+                    });
+                }
+                _ => nodes.push(Node::Token(token)),
+            }
+        }
+
+        nodes
+    }
+
+    /// Keeps `trivia` if it is a comment, and moves `line` past it.
+    fn collect_comment(trivia: Trivia, line: &mut usize, comments: &mut Vec<Comment>) {
+        let start = *line;
+        *line += trivia.text.matches('\n').count();
+
+        if matches!(
+            trivia.kind,
+            TriviaKind::LineComment | TriviaKind::BlockComment
+        ) {
+            comments.push(Comment {
+                text: trivia.text,
+                line: start,
+                end_line: *line,
+            });
+        }
+    }
+
+    fn has_comments(nodes: &[Node]) -> bool {
+        nodes.iter().any(|node| match node {
+            Node::Token(_) => false,
+            Node::Comment { .. } => true,
+            Node::Group { children, .. } => Node::has_comments(children),
+        })
+    }
+}
+
+struct Formatter {
+    lines: Vec<String>,
+    line: String,
+    line_indent: usize,
+    /// Last token written and whether it was a prefix operator.
+    previous: Option<(TokenType, bool)>,
+    after_comment: bool,
+    last_line: Option<usize>,
+    tokens_written: usize,
+    token_count: usize,
+    flat: bool,
+}
+
+impl Formatter {
+    fn new(token_count: usize) -> Self {
+        Self {
+            lines: Vec::new(),
+            line: String::new(),
+            line_indent: 0,
+            previous: None,
+            after_comment: false,
+            last_line: None,
+            tokens_written: 0,
+            token_count,
+            flat: false,
+        }
+    }
+
+    fn finish(mut self) -> Vec<String> {
+        if !self.line.is_empty() {
+            self.newline(0);
+        }
+
+        self.lines
+    }
+
+    fn write_nodes(&mut self, nodes: &[Node], indent: usize) {
+        for node in nodes {
+            match node {
+                Node::Token(token) => self.write_token(token, indent),
+                Node::Comment { comment, ends_line } => {
+                    self.write_comment(comment, *ends_line, indent)
+                }
+                Node::Group {
+                    open,
+                    children,
+                    close,
+                } => {
+                    if self.should_wrap(node, children) {
+                        self.write_wrapped_call(open, children, close, indent);
+                    } else {
+                        self.write_token(open, indent);
+                        self.write_nodes(children, indent);
+                        self.write_token(close, indent);
+                    }
+                }
+            }
+        }
+    }
+
+    fn should_wrap(&self, group: &Node, children: &[Node]) -> bool {
+        if self.flat || !self.ends_operand() || children.is_empty() || Node::has_comments(children)
+        {
+            return false;
+        }
+
+        let mut scratch = Formatter::new(usize::MAX);
+        scratch.previous = self.previous.clone();
+        scratch.flat = true;
+        scratch.write_nodes(std::slice::from_ref(group), 0);
+
+        self.width() + scratch.line.len() > MAX_WIDTH
+    }
+
+    /// Puts each argument of a call that is too long on its own line.
+    fn write_wrapped_call(
+        &mut self,
+        open: &Token,
+        arguments: &[Node],
+        close: &Token,
+        indent: usize,
+    ) {
+        self.write_token(open, indent);
+
+        let arguments = arguments
+            .split(
+                |node| matches!(node, Node::Token(token) if token.token_type == TokenType::Comma),
+            )
+            .collect::<Vec<_>>();
+
+        for (index, argument) in arguments.iter().enumerate() {
+            self.newline(indent + 1);
+            self.write_nodes(argument, indent + 1);
+
+            if index + 1 < arguments.len() {
+                self.line.push(',');
+                self.previous = Some((TokenType::Comma, false));
+            }
+        }
+
+        self.newline(indent);
+        self.write_token(close, indent);
+    }
+
+    fn write_token(&mut self, token: &Token, indent: usize) {
+        if self.line.is_empty() {
+            self.line = INDENT.repeat(self.line_indent);
+        } else if self.after_comment || self.needs_space(&token.token_type) {
+            self.line.push(' ');
+        }
+
+        self.line.push_str(&token.lexeme);
+
+        let is_prefix = matches!(
+            token.token_type,
+            TokenType::Minus
+                | TokenType::Bang
+                | TokenType::Tilde
+                | TokenType::PlusPlus
+                | TokenType::MinusMinus
+        ) && !self.ends_operand();
+
+        self.previous = Some((token.token_type.clone(), is_prefix));
+        self.after_comment = false;
+        self.last_line = Some(token.line);
+        self.tokens_written += 1;
+        self.line_indent = self.continuation_indent(indent);
+    }
+
+    fn write_comment(&mut self, comment: &Comment, ends_line: bool, indent: usize) {
+        let own_line = self.last_line.is_none_or(|line| comment.line > line);
+
+        if own_line {
+            let blank_line = self.last_line.is_some_and(|line| comment.line > line + 1);
+
+            if !self.line.is_empty() {
+                self.newline(self.line_indent);
+            }
+            if blank_line {
+                self.lines.push(String::new());
+            }
+
+            self.line = INDENT.repeat(self.line_indent);
+        } else {
+            self.line.push(' ');
+        }
+
+        self.line.push_str(&comment.text);
+        self.last_line = Some(comment.end_line);
+
+        if ends_line {
+            self.newline(self.continuation_indent(indent));
+        } else {
+            self.after_comment = true;
+        }
+    }
+
+    fn newline(&mut self, indent: usize) {
+        self.lines.push(std::mem::take(&mut self.line));
+        self.line_indent = indent;
+    }
+
+    /// Indent of a line that breaks the expression, which is none before the
+    /// first token and after the last.
+    fn continuation_indent(&self, indent: usize) -> usize {
+        if self.tokens_written == 0 || self.tokens_written >= self.token_count {
+            indent
+        } else {
+            indent + 1
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.line.chars().count()
+    }
+
+    fn ends_operand(&self) -> bool {
+        matches!(
+            self.previous,
+            Some((
+                TokenType::Identifier
+                    | TokenType::Number
+                    | TokenType::String
+                    | TokenType::True
+                    | TokenType::False
+                    | TokenType::Nil
+                    | TokenType::RightParen
+                    | TokenType::RightBrace,
+                _
+            )) | Some((TokenType::PlusPlus | TokenType::MinusMinus, false))
+        )
+    }
+
+    fn needs_space(&self, next: &TokenType) -> bool {
+        let Some((previous, is_prefix)) = &self.previous else {
+            return false;
+        };
+
+        // `- -x` and `- --x` would scan differently without the space.
+        if *is_prefix {
+            return *previous == TokenType::Minus
+                && matches!(next, TokenType::Minus | TokenType::MinusMinus);
+        }
+
+        if *previous == TokenType::LeftParen {
+            return false;
+        }
+
+        match next {
+            TokenType::RightParen | TokenType::Comma | TokenType::Semicolon => false,
+            TokenType::LeftParen | TokenType::PlusPlus | TokenType::MinusMinus => {
+                !self.ends_operand()
+            }
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lint::Lints, messages::Locale};
+
+    use super::*;
+
+    fn format_source(source: &str) -> String {
+        format(
+            source,
+            &mut ErrorHandler::new(Locale::default(), Lints::default()),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_spacing() {
+        assert_eq!(format_source("1+-2*(3-x)"), "1 + -2 * (3 - x)\n");
+        assert_eq!(
+            format_source("!!true?f( 1,2 ):(a ,b)"),
+            "!!true ? f(1, 2) : (a, b)\n"
+        );
+        assert_eq!(format_source("1.50   ==\n1.5"), "1.50 == 1.5\n");
+        assert_eq!(
+            format_source("((a,b)=>-a+ b)( 1,2)"),
+            "((a, b) => -a + b)(1, 2)\n"
+        );
+        assert_eq!(
+            format_source("fun(a,b){return a+b;}(1,2)"),
+            "fun (a, b) { return a + b; }(1, 2)\n"
+        );
+        assert_eq!(format_source("~x&1<<2"), "~x & 1 << 2\n");
+        assert_eq!(
+            format_source("(++n,n++ +n,n*=-1,- -n,- --n)"),
+            "(++n, n++ + n, n *= -1, - -n, - --n)\n"
+        );
+    }
+
+    #[test]
+    fn test_comments() {
+        let source = "// header\n\n// more\n1 + // middle\n/* inline */ 2 // trailing\n";
+
+        assert_eq!(
+            format_source(source),
+            "// header\n\n// more\n1 + // middle\n    /* inline */ 2 // trailing\n"
+        );
+    }
+
+    #[test]
+    fn test_wrapping() {
+        let source = "f(\"a rather long first argument\", \"another long argument, too\", g(\"nested\", -1), x)";
+
+        assert_eq!(
+            format_source(source),
+            "f(\n    \"a rather long first argument\",\n    \"another long argument, too\",\n    g(\"nested\", -1),\n    x\n)\n"
+        );
+    }
+
+    #[test]
+    fn test_unparsed_source() {
+        for source in ["1 )", "(1))", "f(1))", "(1"] {
+            let mut error_handler = ErrorHandler::new(Locale::default(), Lints::default());
+
+            assert_eq!(format(source, &mut error_handler), None, "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_idempotent() {
+        let source = "#!/usr/bin/env rlox\n/* a\n b */ f(1,\n2) // c\n  // d\n";
+        let once = format_source(source);
+
+        assert_eq!(format_source(&once), once);
+    }
+}
@@ -0,0 +1,52 @@
+use std::{
+    cell::RefCell,
+    fmt::{self, Debug, Display},
+    rc::Rc,
+};
+
+use crate::{environment::Environment, expr::Lambda};
+
+/// What stack traces call a function made by a lambda, which has no name.
+pub const LAMBDA_NAME: &str = "<lambda>";
+
+/// A function made by evaluating a lambda, with the parameters in scope
+/// where it was made.
+#[derive(Clone)]
+pub struct Function {
+    pub declaration: Rc<Lambda>,
+    pub closure: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Function {
+    pub fn new(declaration: Lambda, closure: Option<Rc<RefCell<Environment>>>) -> Self {
+        Self {
+            declaration: Rc::new(declaration),
+            closure,
+        }
+    }
+
+    pub fn arity(&self) -> usize {
+        self.declaration.params.len()
+    }
+}
+
+/// Each evaluation of a lambda makes a function equal only to itself.
+impl PartialEq for Function {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.declaration, &other.declaration)
+    }
+}
+
+impl Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<fn>")
+    }
+}
+
+impl Debug for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Function")
+            .field("arity", &self.arity())
+            .finish()
+    }
+}
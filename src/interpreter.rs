@@ -1,120 +1,660 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    io::{self, BufRead, Write},
+    rc::Rc,
+};
+
 use crate::{
+    ast_printer::AstPrinter,
+    compat::{self, Compat},
+    debugger::{self, Frame, Hook},
+    environment::Environment,
     error_handler::{ErrorHandler, RuntimeError},
-    expr::{Binary, Expr, Grouping, Literal, Unary, Visitor},
-    token::LiteralType,
+    expr::{Assign, Binary, Call, Comma, Expr, Increment, Ternary, Unary, Variable, Visitor},
+    function::{Function, LAMBDA_NAME},
+    interrupt,
+    limits::{Budget, Limits},
+    messages::{Locale, Message},
+    native::{self, NativeError, NativeFunction},
+    token::{LiteralType, Token},
     token_type::TokenType,
 };
 
-pub struct Interpreter<'a> {
-    error_handler: &'a mut ErrorHandler,
+pub struct Interpreter {
+    globals: HashMap<String, LiteralType>,
+    locale: Locale,
+    output: Box<dyn Write>,
+    #[allow(dead_code)]
+    input: Box<dyn BufRead>,
+    hook: Option<Box<dyn Hook>>,
+    frames: Vec<Frame>,
+    file: Option<String>,
+    /// The parameters in scope, or `None` outside every function.
+    environment: Option<Rc<RefCell<Environment>>>,
+    /// Nesting of the expression being evaluated, bounded by `max_depth`.
+    depth: usize,
+    limits: Limits,
+    budget: Budget,
+    /// The last line evaluation reached, for errors raised by expressions
+    /// without a token.
+    line: usize,
 }
 
-impl<'a> Interpreter<'a> {
-    pub fn new(error_handler: &'a mut ErrorHandler) -> Self {
-        Self { error_handler }
+impl Interpreter {
+    pub fn new(script_args: Vec<String>) -> Self {
+        let mut interpreter = Self {
+            globals: HashMap::new(),
+            locale: Locale::default(),
+            output: Box::new(io::stdout()),
+            input: Box::new(io::BufReader::new(io::stdin())),
+            hook: None,
+            frames: Vec::new(),
+            file: None,
+            environment: None,
+            depth: 0,
+            limits: Limits::default(),
+            budget: Budget::new(Limits::default()),
+            line: 1,
+        };
+
+        native::define_globals(&mut interpreter, script_args);
+
+        interpreter
+    }
+
+    pub fn define_native<F>(&mut self, name: &str, arity: usize, function: F)
+    where
+        F: Fn(&[LiteralType]) -> Result<LiteralType, String> + 'static,
+    {
+        self.define_global(
+            name,
+            LiteralType::NativeFunction(NativeFunction::new(name, arity, function)),
+        );
+    }
+
+    /// Sets the language and compat mode. jlox only has `clock()`, so
+    /// switching to it removes the other natives for good.
+    pub fn set_locale(&mut self, locale: Locale) {
+        if locale.compat == Compat::Jlox {
+            for name in native::EXTENSIONS {
+                self.globals.remove(name);
+            }
+        }
+
+        self.locale = locale;
+    }
+
+    pub fn locale(&self) -> Locale {
+        self.locale
+    }
+
+    pub fn set_output(&mut self, output: Box<dyn Write>) {
+        self.output = output;
+    }
+
+    pub fn set_input(&mut self, input: Box<dyn BufRead>) {
+        self.input = input;
+    }
+
+    /// Names the file the scripts come from, for stack traces.
+    pub fn set_file(&mut self, file: Option<String>) {
+        self.file = file;
+    }
+
+    /// Defines the natives that reach into the host process, unless the
+    /// sandbox has replaced them or jlox doesn't have them.
+    pub fn define_process_natives(&mut self) {
+        if !self.limits.sandbox && self.locale.compat != Compat::Jlox {
+            native::define_process_natives(self);
+        }
+    }
+
+    /// Limits the scripts evaluated from now on. A sandbox can't be lifted:
+    /// it replaces natives, and stays on whatever later limits say.
+    pub fn set_limits(&mut self, limits: Limits) {
+        // jlox has none of the natives the sandbox replaces.
+        if limits.sandbox && self.locale.compat != Compat::Jlox {
+            native::sandbox(self);
+        }
+
+        self.limits = Limits {
+            sandbox: self.limits.sandbox || limits.sandbox,
+            ..limits
+        };
+    }
+
+    pub fn set_hook(&mut self, hook: Option<Box<dyn Hook>>) {
+        self.hook = hook;
+    }
+
+    /// The expressions being evaluated, innermost last. Only tracked while a
+    /// hook is installed.
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    pub fn define_global(&mut self, name: &str, value: LiteralType) {
+        self.globals.insert(name.to_string(), value);
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<LiteralType> {
+        self.globals.get(name).cloned()
+    }
+
+    pub fn global_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.globals.keys().cloned().collect();
+        names.sort();
+
+        names
+    }
+
+    /// The parameters in reach of the expression being evaluated, sorted by
+    /// name, with the values they have there.
+    pub fn locals(&self) -> Vec<(String, LiteralType)> {
+        self.environment
+            .as_ref()
+            .map(|environment| environment.borrow().visible().into_iter().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn interpret(&mut self, expression: Expr, error_handler: &mut ErrorHandler) {
+        let value = self.evaluate_script(expression);
+
+        let written = match value {
+            Ok(result) => {
+                let text = self.stringify(result);
+                writeln!(self.output, "{}", text)
+            }
+            Err(err) => {
+                error_handler.runtime_error(err);
+                Ok(())
+            }
+        };
+
+        written
+            .and_then(|_| self.output.flush())
+            .expect("Error writing the program output");
+    }
+
+    /// Evaluates the top level of a script, whose frame ends the stack trace
+    /// of any runtime error.
+    pub fn evaluate_script(&mut self, expr: Expr) -> Result<LiteralType, RuntimeError> {
+        self.budget = Budget::new(self.limits);
+        self.evaluate(expr)
+            .map_err(|err| err.leave_script(self.file.as_deref()))
     }
 
-    pub fn interpret(&mut self, expression: Expr) {
-        let value = Self::evaluate(expression);
+    pub fn evaluate(&mut self, expr: Expr) -> Result<LiteralType, RuntimeError> {
+        if let Some(token) = debugger::stop_token(&expr) {
+            self.line = token.line;
+        }
 
-        match value {
-            Ok(result) => println!("{:?}", result),
-            Err(err) => self.error_handler.runtime_error(err),
+        if self.depth >= self.limits.max_depth {
+            return Err(self.error_at_line(Message::StackOverflow));
         }
+
+        self.budget
+            .step()
+            .map_err(|message| self.error_at_line(message))?;
+
+        self.depth += 1;
+        let result = if self.hook.is_none() {
+            self.visit(expr)
+        } else {
+            self.evaluate_traced(expr)
+        };
+        self.depth -= 1;
+
+        result
     }
 
-    fn evaluate(expr: Expr) -> Result<LiteralType, RuntimeError> {
-        Interpreter::visit(expr)
+    /// Evaluates `expr` as its own frame, giving the hook a chance to pause
+    /// first. The hook is taken out meanwhile, so whatever it evaluates runs
+    /// untraced.
+    fn evaluate_traced(&mut self, expr: Expr) -> Result<LiteralType, RuntimeError> {
+        let Some(token) = debugger::stop_token(&expr).cloned() else {
+            return self.visit(expr);
+        };
+
+        self.frames.push(Frame {
+            line: token.line,
+            column: token.column,
+            expression: AstPrinter {}.visit(expr.clone()),
+        });
+
+        let mut hook = self.hook.take().expect("traced without a hook");
+        let resume = hook.pause(self);
+        self.hook = Some(hook);
+
+        let result = if resume {
+            self.visit(expr)
+        } else {
+            Err(self.error(token, Message::StoppedByDebugger, &[]))
+        };
+
+        self.frames.pop();
+
+        result
     }
 
-    fn evaluate_unary(unary: Unary) -> Result<LiteralType, RuntimeError> {
+    fn evaluate_unary(&mut self, unary: Unary) -> Result<LiteralType, RuntimeError> {
         let Unary { operator, right } = unary;
 
-        let evaluated_right = Interpreter::evaluate(*right)?;
+        if interrupt::is_interrupted() {
+            return Err(self.error(operator, Message::Interrupted, &[]));
+        }
+
+        let evaluated_right = self.evaluate(*right)?;
 
         match operator.token_type {
-            TokenType::Minus => {
-                return Ok(LiteralType::Number(-evaluated_right.as_number(operator)?));
+            TokenType::Minus => Ok(LiteralType::Number(
+                -self.number_operand(&operator, &evaluated_right)?,
+            )),
+            TokenType::Bang => Ok(LiteralType::Bool(!Interpreter::is_truthy(evaluated_right))),
+            TokenType::Tilde => {
+                let value = self.number_operand(&operator, &evaluated_right)?;
+                let value = Interpreter::integer(value)
+                    .ok_or_else(|| self.error(operator, Message::OperandMustBeInteger, &[]))?;
+
+                Ok(LiteralType::Number(!value as f64))
             }
-            TokenType::Bang => Ok(LiteralType::Bool(Interpreter::is_truthy(evaluated_right))),
             _ => unreachable!(),
         }
     }
 
-    fn evaluate_binary(binary: Binary) -> Result<LiteralType, RuntimeError> {
+    fn evaluate_binary(&mut self, binary: Binary) -> Result<LiteralType, RuntimeError> {
         let Binary {
             left,
             operator,
             right,
         } = binary;
 
-        let evaluated_left = Interpreter::evaluate(*left)?;
-        let evaluated_right = Interpreter::evaluate(*right)?;
+        if interrupt::is_interrupted() {
+            return Err(self.error(operator, Message::Interrupted, &[]));
+        }
 
+        let evaluated_left = self.evaluate(*left)?;
+        let evaluated_right = self.evaluate(*right)?;
+
+        self.apply_binary(operator, evaluated_left, evaluated_right)
+    }
+
+    fn apply_binary(
+        &mut self,
+        operator: Token,
+        evaluated_left: LiteralType,
+        evaluated_right: LiteralType,
+    ) -> Result<LiteralType, RuntimeError> {
         match operator.token_type {
-            TokenType::Minus => Ok(LiteralType::Number(
-                evaluated_left.as_number(operator.clone())?
-                    - evaluated_right.as_number(operator)?,
-            )),
-            TokenType::Slash => Ok(LiteralType::Number(
-                evaluated_left.as_number(operator.clone())?
-                    / evaluated_right.as_number(operator)?,
-            )),
-            TokenType::Star => Ok(LiteralType::Number(
-                evaluated_left.as_number(operator.clone())?
-                    * evaluated_right.as_number(operator)?,
-            )),
             TokenType::Plus => match (evaluated_left, evaluated_right) {
                 (LiteralType::Number(left_value), LiteralType::Number(right_value)) => {
                     Ok(LiteralType::Number(left_value + right_value))
                 }
                 (LiteralType::String(left_value), LiteralType::String(right_value)) => {
+                    self.budget
+                        .allocate(left_value.len() + right_value.len())
+                        .map_err(|message| self.error(operator, message, &[]))?;
+
                     Ok(LiteralType::String(left_value + &right_value))
                 }
-                _ => Err(RuntimeError::new(
-                    operator,
-                    "Operands must be two numbers or two strings.",
-                )),
+                _ => Err(self.error(operator, Message::OperandsMustBeNumbersOrStrings, &[])),
             },
-            TokenType::Greater => Ok(LiteralType::Bool(
-                evaluated_left.as_number(operator.clone())?
-                    > evaluated_right.as_number(operator)?,
+            TokenType::BangEqual => Ok(LiteralType::Bool(
+                !self.is_equal(&evaluated_left, &evaluated_right),
             )),
-            TokenType::GreaterEqual => Ok(LiteralType::Bool(
-                evaluated_left.as_number(operator.clone())?
-                    >= evaluated_right.as_number(operator)?,
+            TokenType::EqualEqual => Ok(LiteralType::Bool(
+                self.is_equal(&evaluated_left, &evaluated_right),
             )),
-            TokenType::Less => Ok(LiteralType::Bool(
-                evaluated_left.as_number(operator.clone())?
-                    < evaluated_right.as_number(operator)?,
-            )),
-            TokenType::LessEqual => Ok(LiteralType::Bool(
-                evaluated_left.as_number(operator.clone())?
-                    <= evaluated_right.as_number(operator)?,
+            TokenType::Ampersand
+            | TokenType::Pipe
+            | TokenType::Caret
+            | TokenType::LessLess
+            | TokenType::GreaterGreater => {
+                self.evaluate_bitwise(&operator, &evaluated_left, &evaluated_right)
+            }
+            _ => {
+                let (left_value, right_value) =
+                    self.number_operands(&operator, &evaluated_left, &evaluated_right)?;
+
+                match operator.token_type {
+                    TokenType::Minus => Ok(LiteralType::Number(left_value - right_value)),
+                    TokenType::Slash => Ok(LiteralType::Number(left_value / right_value)),
+                    TokenType::Star => Ok(LiteralType::Number(left_value * right_value)),
+                    TokenType::Percent => Ok(LiteralType::Number(Interpreter::floored_modulo(
+                        left_value,
+                        right_value,
+                    ))),
+                    TokenType::StarStar => Ok(LiteralType::Number(left_value.powf(right_value))),
+                    TokenType::Div => Ok(LiteralType::Number((left_value / right_value).floor())),
+                    TokenType::Greater => Ok(LiteralType::Bool(left_value > right_value)),
+                    TokenType::GreaterEqual => Ok(LiteralType::Bool(left_value >= right_value)),
+                    TokenType::Less => Ok(LiteralType::Bool(left_value < right_value)),
+                    TokenType::LessEqual => Ok(LiteralType::Bool(left_value <= right_value)),
+                    _ => unreachable!(),
+                }
+            }
+        }
+    }
+
+    /// Applies the operator of `x += value` as if it were `x + value`, then
+    /// stores the result back in `x`.
+    fn evaluate_assign(&mut self, assign: Assign) -> Result<LiteralType, RuntimeError> {
+        let Assign {
+            name,
+            operator,
+            value,
+        } = assign;
+
+        let current = self.evaluate_variable(Variable { name: name.clone() })?;
+        let value = self.evaluate(*value)?;
+
+        let token_type = match operator.token_type {
+            TokenType::PlusEqual => TokenType::Plus,
+            TokenType::MinusEqual => TokenType::Minus,
+            TokenType::StarEqual => TokenType::Star,
+            TokenType::SlashEqual => TokenType::Slash,
+            TokenType::PercentEqual => TokenType::Percent,
+            _ => unreachable!(),
+        };
+        let operator = Token {
+            token_type,
+            ..operator
+        };
+
+        let result = self.apply_binary(operator, current, value)?;
+        self.assign(&name.lexeme, result.clone());
+
+        Ok(result)
+    }
+
+    fn evaluate_increment(&mut self, increment: Increment) -> Result<LiteralType, RuntimeError> {
+        let Increment {
+            name,
+            operator,
+            prefix,
+        } = increment;
+
+        let current = self.evaluate_variable(Variable { name: name.clone() })?;
+        let current = self.number_operand(&operator, &current)?;
+        let updated = match operator.token_type {
+            TokenType::PlusPlus => current + 1.0,
+            TokenType::MinusMinus => current - 1.0,
+            _ => unreachable!(),
+        };
+
+        self.assign(&name.lexeme, LiteralType::Number(updated));
+
+        Ok(LiteralType::Number(if prefix { updated } else { current }))
+    }
+
+    /// Stores `value` in the innermost environment that defines `name`, or
+    /// defines it as a global when none does.
+    pub fn assign(&mut self, name: &str, value: LiteralType) {
+        if let Some(environment) = &self.environment
+            && environment.borrow_mut().assign(name, &value)
+        {
+            return;
+        }
+
+        self.globals.insert(name.to_string(), value);
+    }
+
+    fn evaluate_bitwise(
+        &self,
+        operator: &Token,
+        left: &LiteralType,
+        right: &LiteralType,
+    ) -> Result<LiteralType, RuntimeError> {
+        let (left_value, right_value) = self.number_operands(operator, left, right)?;
+        let (Some(left_value), Some(right_value)) = (
+            Interpreter::integer(left_value),
+            Interpreter::integer(right_value),
+        ) else {
+            return Err(self.error(operator.clone(), Message::OperandsMustBeIntegers, &[]));
+        };
+
+        let result = match operator.token_type {
+            TokenType::Ampersand => left_value & right_value,
+            TokenType::Pipe => left_value | right_value,
+            TokenType::Caret => left_value ^ right_value,
+            TokenType::LessLess if right_value >= 0 => {
+                let shifted = (left_value as i128) << right_value.min(64);
+                Interpreter::integer(shifted as f64)
+                    .ok_or_else(|| self.error(operator.clone(), Message::ShiftOutOfRange, &[]))?
+            }
+            TokenType::GreaterGreater if right_value >= 0 => left_value >> right_value.min(63),
+            TokenType::LessLess | TokenType::GreaterGreater => {
+                return Err(self.error(operator.clone(), Message::ShiftOutOfRange, &[]));
+            }
+            _ => unreachable!(),
+        };
+
+        Ok(LiteralType::Number(result as f64))
+    }
+
+    fn number_operand(&self, operator: &Token, operand: &LiteralType) -> Result<f64, RuntimeError> {
+        match operand {
+            LiteralType::Number(value) => Ok(*value),
+            _ => Err(self.error(operator.clone(), Message::OperandMustBeNumber, &[])),
+        }
+    }
+
+    fn number_operands(
+        &self,
+        operator: &Token,
+        left: &LiteralType,
+        right: &LiteralType,
+    ) -> Result<(f64, f64), RuntimeError> {
+        match (left, right, self.locale.compat) {
+            (LiteralType::Number(left_value), LiteralType::Number(right_value), _) => {
+                Ok((*left_value, *right_value))
+            }
+            (_, _, Compat::Jlox) => {
+                Err(self.error(operator.clone(), Message::OperandsMustBeNumbers, &[]))
+            }
+            (_, _, Compat::Rlox) => Ok((
+                self.number_operand(operator, left)?,
+                self.number_operand(operator, right)?,
             )),
-            TokenType::BangEqual => Ok(LiteralType::Bool(evaluated_left != evaluated_right)),
-            TokenType::EqualEqual => Ok(LiteralType::Bool(evaluated_left == evaluated_right)),
+        }
+    }
+
+    fn is_equal(&self, left: &LiteralType, right: &LiteralType) -> bool {
+        match (left, right, self.locale.compat) {
+            (LiteralType::Number(left_value), LiteralType::Number(right_value), Compat::Jlox) => {
+                compat::java_number_equals(*left_value, *right_value)
+            }
+            _ => left == right,
+        }
+    }
+
+    pub fn stringify(&self, value: LiteralType) -> String {
+        match (value, self.locale.compat) {
+            (LiteralType::NativeFunction(function), _) => function.to_string(),
+            (LiteralType::Function(function), _) => function.to_string(),
+            (value, Compat::Rlox) => format!("{:?}", value),
+            (LiteralType::Number(number), Compat::Jlox) => compat::java_number(number),
+            (LiteralType::Nil, Compat::Jlox) => String::from("nil"),
+            (value, Compat::Jlox) => value.to_string(),
+        }
+    }
+
+    fn evaluate_call(&mut self, call: Call) -> Result<LiteralType, RuntimeError> {
+        let Call {
+            callee,
+            paren,
+            arguments,
+        } = call;
+
+        if interrupt::is_interrupted() {
+            return Err(self.error(paren, Message::Interrupted, &[]));
+        }
+
+        let callee = self.evaluate(*callee)?;
+        let arguments = arguments
+            .into_iter()
+            .map(|argument| self.evaluate(argument))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let arity = match &callee {
+            LiteralType::NativeFunction(function) => function.arity,
+            LiteralType::Function(function) => function.arity(),
+            _ => return Err(self.error(paren, Message::NotCallable, &[])),
+        };
+
+        if arguments.len() != arity {
+            return Err(self.error(
+                paren,
+                Message::WrongArity,
+                &[&arity.to_string(), &arguments.len().to_string()],
+            ));
+        }
+
+        match callee {
+            LiteralType::Function(function) => self.call_function(function, arguments, paren.line),
+            LiteralType::NativeFunction(function) => self.call_native(function, arguments, paren),
             _ => unreachable!(),
         }
     }
 
+    /// Evaluates the body of `function` with its parameters bound to
+    /// `arguments`, in a scope enclosed by the one it was made in.
+    fn call_function(
+        &mut self,
+        function: Function,
+        arguments: Vec<LiteralType>,
+        line: usize,
+    ) -> Result<LiteralType, RuntimeError> {
+        let mut environment = Environment::new(function.closure.clone());
+        for (param, argument) in function.declaration.params.iter().zip(arguments) {
+            environment.define(&param.lexeme, argument);
+        }
+
+        let enclosing = self.environment.replace(Rc::new(RefCell::new(environment)));
+        let result = self.evaluate(*function.declaration.body.clone());
+        self.environment = enclosing;
+
+        result.map_err(|err| err.leave_function(LAMBDA_NAME, self.file.as_deref(), line))
+    }
+
+    fn call_native(
+        &mut self,
+        function: NativeFunction,
+        arguments: Vec<LiteralType>,
+        paren: Token,
+    ) -> Result<LiteralType, RuntimeError> {
+        let line = paren.line;
+        let value = function.call(&arguments).map_err(|err| {
+            let error = match err {
+                NativeError::Custom(message) => RuntimeError::new(paren.clone(), &message),
+                NativeError::Message(message) => self.error(paren.clone(), message, &[]),
+                NativeError::Exit(code) => {
+                    self.output.flush().ok();
+                    return RuntimeError::exit(line, code);
+                }
+            };
+
+            error.leave_function(&function.name, None, line)
+        })?;
+
+        if let LiteralType::String(text) = &value {
+            self.budget
+                .allocate(text.len())
+                .map_err(|message| self.error(paren, message, &[]))?;
+        }
+
+        Ok(value)
+    }
+
+    fn evaluate_variable(&self, variable: Variable) -> Result<LiteralType, RuntimeError> {
+        let Variable { name } = variable;
+
+        let local = self
+            .environment
+            .as_ref()
+            .and_then(|environment| environment.borrow().get(&name.lexeme));
+
+        match local.or_else(|| self.globals.get(&name.lexeme).cloned()) {
+            Some(value) => Ok(value),
+            None => {
+                let lexeme = name.lexeme.clone();
+                Err(self.error(name, Message::UndefinedVariable, &[&lexeme]))
+            }
+        }
+    }
+
+    fn error(&self, token: Token, message: Message, args: &[&str]) -> RuntimeError {
+        RuntimeError::new(token, &self.locale.text(message, args)).with_key(message)
+    }
+
+    /// An error on the last line evaluation reached, for limits that any
+    /// expression can exceed.
+    fn error_at_line(&self, message: Message) -> RuntimeError {
+        RuntimeError::at_line(self.line, &self.locale.text(message, &[])).with_key(message)
+    }
+
+    /// `value` as an integer, if it is whole and fits in 54-bit two's
+    /// complement, where every integer has an exact `f64`.
+    fn integer(value: f64) -> Option<i64> {
+        const LIMIT: f64 = (1u64 << 53) as f64;
+
+        (value.fract() == 0.0 && (-LIMIT..LIMIT).contains(&value)).then_some(value as i64)
+    }
+
+    /// The remainder of flooring division, which takes the sign of the
+    /// divisor, so that `a == b * (a div b) + a % b`.
+    fn floored_modulo(dividend: f64, divisor: f64) -> f64 {
+        let remainder = dividend % divisor;
+
+        if remainder != 0.0 && (remainder < 0.0) != (divisor < 0.0) {
+            remainder + divisor
+        } else {
+            remainder
+        }
+    }
+
     fn is_truthy(literal: LiteralType) -> bool {
         match literal {
-            LiteralType::Nil => return false,
-            LiteralType::Bool(value) => return value,
+            LiteralType::Nil => false,
+            LiteralType::Bool(value) => value,
             _ => true,
         }
     }
 }
 
-impl<'a> Visitor<Result<LiteralType, RuntimeError>> for Interpreter<'a> {
-    fn visit(expr: Expr) -> Result<LiteralType, RuntimeError> {
+impl Visitor<Result<LiteralType, RuntimeError>> for Interpreter {
+    fn visit(&mut self, expr: Expr) -> Result<LiteralType, RuntimeError> {
         match expr {
-            Expr::Literal(Literal { value }) => Ok(value),
-            Expr::Grouping(Grouping { expression }) => Interpreter::evaluate(*expression),
-            Expr::Unary(unary) => Interpreter::evaluate_unary(unary),
-            Expr::Binary(binary) => Interpreter::evaluate_binary(binary),
-            _ => todo!(),
+            Expr::Literal(literal) => Ok(literal.value),
+            Expr::Grouping(grouping) => self.evaluate(*grouping.expression),
+            Expr::Unary(unary) => self.evaluate_unary(*unary),
+            Expr::Binary(binary) => self.evaluate_binary(*binary),
+            Expr::Comma(comma) => {
+                let Comma { left, right } = *comma;
+                self.evaluate(*left)?;
+                self.evaluate(*right)
+            }
+            Expr::Ternary(ternary) => {
+                let Ternary {
+                    condition,
+                    then_branch,
+                    else_branch,
+                } = *ternary;
+                if Interpreter::is_truthy(self.evaluate(*condition)?) {
+                    self.evaluate(*then_branch)
+                } else {
+                    self.evaluate(*else_branch)
+                }
+            }
+            Expr::Call(call) => self.evaluate_call(*call),
+            Expr::Variable(variable) => self.evaluate_variable(*variable),
+            Expr::Lambda(lambda) => Ok(LiteralType::Function(Function::new(
+                *lambda,
+                self.environment.clone(),
+            ))),
+            Expr::Assign(assign) => self.evaluate_assign(*assign),
+            Expr::Increment(increment) => self.evaluate_increment(*increment),
         }
     }
 }
@@ -123,28 +663,90 @@ impl<'a> Visitor<Result<LiteralType, RuntimeError>> for Interpreter<'a> {
 mod tests {
 
     use super::*;
+    use crate::expr::{Grouping, Literal};
 
     #[test]
     fn test_literal() {
-        let expr = Expr::Literal(Literal {
+        let expr = Expr::Literal(Box::new(Literal {
             value: LiteralType::String(String::from("Teste")),
-        });
+        }));
 
-        let result = Interpreter::visit(expr);
+        let result = Interpreter::new(vec![]).visit(expr);
 
         assert_eq!(result.unwrap(), LiteralType::String(String::from("Teste")));
     }
 
     #[test]
     fn test_grouping() {
-        let expr = Expr::Grouping(Grouping {
-            expression: Box::new(Expr::Literal(Literal {
+        let expr = Expr::Grouping(Box::new(Grouping {
+            expression: Box::new(Expr::Literal(Box::new(Literal {
                 value: LiteralType::Number(123.into()),
-            })),
-        });
+            }))),
+        }));
 
-        let result = Interpreter::visit(expr);
+        let result = Interpreter::new(vec![]).visit(expr);
 
         assert_eq!(result.unwrap(), LiteralType::Number(123.into()));
     }
+
+    #[test]
+    fn test_native_call() {
+        let expr = Expr::Call(Box::new(Call {
+            callee: Box::new(Expr::Variable(Box::new(Variable {
+                name: Token::new(
+                    TokenType::Identifier,
+                    String::from("args"),
+                    LiteralType::None,
+                    1,
+                    1,
+                ),
+            }))),
+            paren: Token::new(
+                TokenType::RightParen,
+                String::from(")"),
+                LiteralType::None,
+                1,
+                6,
+            ),
+            arguments: vec![],
+        }));
+
+        let script_args = vec![String::from("a"), String::from("b")];
+        let result = Interpreter::new(script_args).visit(expr);
+
+        assert_eq!(result.unwrap(), LiteralType::String(String::from("a b")));
+    }
+
+    #[test]
+    fn test_define_native() {
+        let expr = Expr::Call(Box::new(Call {
+            callee: Box::new(Expr::Variable(Box::new(Variable {
+                name: Token::new(
+                    TokenType::Identifier,
+                    String::from("double"),
+                    LiteralType::None,
+                    1,
+                    1,
+                ),
+            }))),
+            paren: Token::new(
+                TokenType::RightParen,
+                String::from(")"),
+                LiteralType::None,
+                1,
+                9,
+            ),
+            arguments: vec![Expr::Literal(Box::new(Literal {
+                value: LiteralType::Number(21.0),
+            }))],
+        }));
+
+        let mut interpreter = Interpreter::new(vec![]);
+        interpreter.define_native("double", 1, |arguments| match arguments[0] {
+            LiteralType::Number(value) => Ok(LiteralType::Number(value * 2.0)),
+            _ => Err(String::from("Operand must be a number.")),
+        });
+
+        assert_eq!(interpreter.visit(expr).unwrap(), LiteralType::Number(42.0));
+    }
 }
@@ -0,0 +1,47 @@
+use std::{
+    io::{self, Write},
+    sync::{
+        Arc, LazyLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+};
+
+use signal_hook::{consts::SIGINT, iterator::Signals};
+
+static INTERRUPTED: LazyLock<Arc<AtomicBool>> = LazyLock::new(|| Arc::new(AtomicBool::new(false)));
+
+/// Set while the REPL waits for a line, when there is nothing to interrupt.
+static PROMPTING: AtomicBool = AtomicBool::new(false);
+
+pub fn install() {
+    signal_hook::flag::register(SIGINT, Arc::clone(&INTERRUPTED))
+        .expect("Error installing the SIGINT handler");
+}
+
+/// Answers Ctrl-C at an idle prompt like a shell does, by printing `^C` and
+/// `prompt` again. The terminal discards the line being typed.
+pub fn watch_prompt(prompt: &'static str) {
+    let mut signals = Signals::new([SIGINT]).expect("Error installing the SIGINT handler");
+
+    thread::spawn(move || {
+        for _ in signals.forever() {
+            if PROMPTING.load(Ordering::Relaxed) {
+                let mut stdout = io::stdout().lock();
+                let _ = write!(stdout, "^C\n{}", prompt).and_then(|_| stdout.flush());
+            }
+        }
+    });
+}
+
+pub fn set_prompting(prompting: bool) {
+    PROMPTING.store(prompting, Ordering::Relaxed);
+}
+
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::Relaxed)
+}
+
+pub fn reset() {
+    INTERRUPTED.store(false, Ordering::Relaxed);
+}
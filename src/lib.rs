@@ -0,0 +1,39 @@
+mod ast_json;
+mod ast_printer;
+mod compat;
+mod cst;
+mod dap;
+mod debugger;
+mod environment;
+mod error_handler;
+mod explain;
+mod expr;
+mod formatter;
+mod function;
+mod interpreter;
+pub mod interrupt;
+mod limits;
+mod lint;
+mod lox;
+mod lsp;
+mod messages;
+mod native;
+mod parser;
+mod protocol;
+mod scanner;
+mod token;
+mod token_type;
+
+pub use compat::Compat;
+pub use cst::{SyntaxElement, SyntaxKind, SyntaxNode};
+pub use dap::serve as serve_dap;
+pub use error_handler::{Diagnostic, DiagnosticKind, Location, StackFrame, exit_code};
+pub use explain::explain;
+pub use limits::{DEFAULT_MAX_DEPTH, Limits, MAX_MAX_DEPTH};
+pub use lint::{Level, Lint, Lints};
+pub use lox::{Dump, Lox};
+pub use lsp::serve as serve_lsp;
+pub use messages::{Language, Locale, Message};
+pub use native::NativeFunction;
+pub use token::{LiteralType, Token, Trivia, TriviaKind};
+pub use token_type::TokenType;
@@ -0,0 +1,133 @@
+use std::time::{Duration, Instant};
+
+use crate::messages::Message;
+
+/// Enough for any expression the parser accepts, and shallow enough for
+/// recursion through lambdas to fit the main thread's 8 MiB stack, even in
+/// debug builds.
+pub const DEFAULT_MAX_DEPTH: usize = 1024;
+
+/// The deepest `max_depth` a script can ask for. Every level costs a call's
+/// worth of native stack, so deeper limits would need stacks of gigabytes.
+pub const MAX_MAX_DEPTH: usize = 10_000;
+
+/// Native stack a level of evaluation can take. A call through a lambda is
+/// the most expensive, at about 7 KiB in debug builds and a third of that in
+/// release ones.
+const STACK_PER_LEVEL: usize = 16 << 10;
+
+/// Native stack for everything but evaluation: scanning, and parsing and
+/// linting the deepest tree the parser accepts.
+const BASE_STACK: usize = 8 << 20;
+
+/// Bounds on what a script can do, for running code that isn't trusted.
+/// Each one a script exceeds fails it with its own runtime error.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+    /// How deeply evaluation can recurse.
+    pub max_depth: usize,
+    /// How many expressions a script can evaluate.
+    pub max_steps: Option<u64>,
+    /// How long a script can run.
+    pub timeout: Option<Duration>,
+    /// How many bytes of strings a script can build, in total.
+    pub max_heap: Option<usize>,
+    /// Whether the natives that reach outside the interpreter fail instead.
+    pub sandbox: bool,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_steps: None,
+            timeout: None,
+            max_heap: None,
+            sandbox: false,
+        }
+    }
+}
+
+impl Limits {
+    /// How much stack a thread needs to run scripts under these limits
+    /// without overflowing it before `max_depth` stops them.
+    pub fn stack_size(&self) -> usize {
+        BASE_STACK.saturating_add(self.max_depth.saturating_mul(STACK_PER_LEVEL))
+    }
+}
+
+/// What a script has used of its limits so far.
+#[derive(Debug, Clone, Copy)]
+pub struct Budget {
+    limits: Limits,
+    steps: u64,
+    heap: usize,
+    deadline: Option<Instant>,
+}
+
+impl Budget {
+    /// Starts the clock on a script.
+    pub fn new(limits: Limits) -> Self {
+        Self {
+            limits,
+            steps: 0,
+            heap: 0,
+            deadline: limits.timeout.map(|timeout| Instant::now() + timeout),
+        }
+    }
+
+    /// Spends a step on evaluating an expression.
+    pub fn step(&mut self) -> Result<(), Message> {
+        self.steps += 1;
+
+        if self.limits.max_steps.is_some_and(|max| self.steps > max) {
+            return Err(Message::StepLimitExceeded);
+        }
+
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Err(Message::TimeLimitExceeded);
+        }
+
+        Ok(())
+    }
+
+    /// Spends `bytes` on a string the script built.
+    pub fn allocate(&mut self, bytes: usize) -> Result<(), Message> {
+        self.heap = self.heap.saturating_add(bytes);
+
+        if self.limits.max_heap.is_some_and(|max| self.heap > max) {
+            return Err(Message::HeapLimitExceeded);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget() {
+        let mut budget = Budget::new(Limits {
+            max_steps: Some(2),
+            max_heap: Some(4),
+            ..Limits::default()
+        });
+
+        assert_eq!(budget.step(), Ok(()));
+        assert_eq!(budget.step(), Ok(()));
+        assert_eq!(budget.step(), Err(Message::StepLimitExceeded));
+        assert_eq!(budget.allocate(4), Ok(()));
+        assert_eq!(budget.allocate(1), Err(Message::HeapLimitExceeded));
+
+        let mut budget = Budget::new(Limits {
+            timeout: Some(Duration::ZERO),
+            ..Limits::default()
+        });
+        assert_eq!(budget.step(), Err(Message::TimeLimitExceeded));
+    }
+}
@@ -0,0 +1,233 @@
+use clap::ValueEnum;
+
+use crate::{
+    error_handler::ErrorHandler,
+    expr::{Binary, Call, Comma, Expr, Lambda, Ternary, Visitor},
+    messages::Message,
+    token::Token,
+    token_type::TokenType,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Lint {
+    #[value(name = "self_comparison")]
+    SelfComparison,
+    #[value(name = "unknown_lints")]
+    UnknownLints,
+    #[value(name = "unused_parameters")]
+    UnusedParameters,
+    #[value(name = "shadowed_parameters")]
+    ShadowedParameters,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 4] = [
+        Lint::SelfComparison,
+        Lint::UnknownLints,
+        Lint::UnusedParameters,
+        Lint::ShadowedParameters,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Lint::from_str(name, false).ok()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+pub enum Level {
+    Allow,
+    #[default]
+    Warn,
+    Deny,
+}
+
+/// The level of every lint. Denied lints are reported as errors.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Lints {
+    levels: [Level; Lint::ALL.len()],
+}
+
+impl Lints {
+    pub fn level(&self, lint: Lint) -> Level {
+        self.levels[lint as usize]
+    }
+
+    pub fn set_level(&mut self, lint: Lint, level: Level) {
+        self.levels[lint as usize] = level;
+    }
+}
+
+/// Parses the body of a `// lox: allow(a, b)` comment into its level and lint
+/// names, or returns `None` when the comment isn't a pragma.
+pub fn parse_pragma(comment: &str) -> Option<(Level, Vec<&str>)> {
+    let pragma = comment.trim().strip_prefix("lox:")?.trim();
+    let (level, names) = pragma.strip_suffix(')')?.split_once('(')?;
+    let level = Level::from_str(level.trim(), false).ok()?;
+
+    let names = names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .collect();
+
+    Some((level, names))
+}
+
+pub fn check(expr: Expr, error_handler: &mut ErrorHandler) {
+    Linter {
+        error_handler,
+        scopes: Vec::new(),
+    }
+    .visit(expr);
+}
+
+struct Linter<'a> {
+    error_handler: &'a mut ErrorHandler,
+    /// The parameters of each enclosing lambda, innermost last, and whether
+    /// each has been read.
+    scopes: Vec<Vec<(Token, bool)>>,
+}
+
+impl Linter<'_> {
+    fn check_lambda(&mut self, lambda: Lambda) {
+        for param in &lambda.params {
+            let shadows = self
+                .scopes
+                .iter()
+                .flatten()
+                .any(|(outer, _)| outer.lexeme == param.lexeme);
+
+            if shadows {
+                self.error_handler.warning(
+                    Lint::ShadowedParameters,
+                    param,
+                    Message::ShadowedParameter,
+                    &[&param.lexeme],
+                );
+            }
+        }
+
+        let params = lambda.params.into_iter().map(|param| (param, false));
+        self.scopes.push(params.collect());
+        self.visit(*lambda.body);
+
+        for (param, used) in self.scopes.pop().unwrap_or_default() {
+            if !used && !param.lexeme.starts_with('_') {
+                self.error_handler.warning(
+                    Lint::UnusedParameters,
+                    &param,
+                    Message::UnusedParameter,
+                    &[&param.lexeme],
+                );
+            }
+        }
+    }
+
+    /// Marks the parameter that `name` reads, if any, as used.
+    fn read(&mut self, name: &Token) {
+        let param = self.scopes.iter_mut().rev().find_map(|scope| {
+            scope
+                .iter_mut()
+                .find(|(param, _)| param.lexeme == name.lexeme)
+        });
+
+        if let Some((_, used)) = param {
+            *used = true;
+        }
+    }
+
+    fn check_binary(&mut self, binary: Binary) {
+        let Binary {
+            left,
+            operator,
+            right,
+        } = binary;
+
+        let is_comparison = matches!(
+            operator.token_type,
+            TokenType::EqualEqual
+                | TokenType::BangEqual
+                | TokenType::Greater
+                | TokenType::GreaterEqual
+                | TokenType::Less
+                | TokenType::LessEqual
+        );
+
+        if let (true, Expr::Variable(left), Expr::Variable(right)) =
+            (is_comparison, left.as_ref(), right.as_ref())
+            && left.name.lexeme == right.name.lexeme
+        {
+            self.error_handler.warning(
+                Lint::SelfComparison,
+                &operator,
+                Message::SelfComparison,
+                &[&left.name.lexeme],
+            );
+        }
+
+        self.visit(*left);
+        self.visit(*right);
+    }
+}
+
+impl Visitor<()> for Linter<'_> {
+    fn visit(&mut self, expr: Expr) {
+        match expr {
+            Expr::Binary(binary) => self.check_binary(*binary),
+            Expr::Grouping(grouping) => self.visit(*grouping.expression),
+            Expr::Lambda(lambda) => self.check_lambda(*lambda),
+            Expr::Unary(unary) => self.visit(*unary.right),
+            Expr::Comma(comma) => {
+                let Comma { left, right } = *comma;
+                self.visit(*left);
+                self.visit(*right);
+            }
+            Expr::Ternary(ternary) => {
+                let Ternary {
+                    condition,
+                    then_branch,
+                    else_branch,
+                } = *ternary;
+                self.visit(*condition);
+                self.visit(*then_branch);
+                self.visit(*else_branch);
+            }
+            Expr::Call(call) => {
+                let Call {
+                    callee, arguments, ..
+                } = *call;
+                self.visit(*callee);
+                arguments
+                    .into_iter()
+                    .for_each(|argument| self.visit(argument));
+            }
+            Expr::Assign(assign) => {
+                // Every assignment is compound, so it reads the name too.
+                self.read(&assign.name);
+                self.visit(*assign.value);
+            }
+            Expr::Variable(variable) => self.read(&variable.name),
+            Expr::Increment(increment) => self.read(&increment.name),
+            Expr::Literal(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pragma() {
+        assert_eq!(
+            parse_pragma(" lox: allow(self_comparison, unknown_lints)"),
+            Some((Level::Allow, vec!["self_comparison", "unknown_lints"]))
+        );
+        assert_eq!(
+            parse_pragma("lox: deny(self_comparison)"),
+            Some((Level::Deny, vec!["self_comparison"]))
+        );
+        assert_eq!(parse_pragma(" lox: forbid(self_comparison)"), None);
+        assert_eq!(parse_pragma(" an ordinary comment"), None);
+    }
+}
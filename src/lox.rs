@@ -0,0 +1,708 @@
+use std::io::{self, BufRead, Write};
+
+use crate::{
+    ast_json::AstJson,
+    ast_printer::AstPrinter,
+    compat::Compat,
+    cst::SyntaxNode,
+    debugger::{Debugger, Hook},
+    error_handler::{Diagnostic, DiagnosticKind, ErrorHandler},
+    expr::Expr,
+    formatter,
+    interpreter::Interpreter,
+    limits::Limits,
+    lint::{self, Level, Lint, Lints},
+    messages::{Language, Locale},
+    parser::Parser,
+    scanner::Scanner,
+    token::LiteralType,
+};
+
+/// What `Lox::dump` prints instead of running a script.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dump {
+    Tokens,
+    Ast,
+    AstJson,
+}
+
+/// Runs Lox sources, reporting their diagnostics.
+///
+/// Scripts run on the calling thread's stack, and evaluation recurses once
+/// per level of `Limits::max_depth`. The default limits fit the main
+/// thread's stack; run anything else on a thread with at least
+/// `Limits::stack_size` bytes of stack, or a deep script overflows it
+/// before the limit stops it.
+pub struct Lox {
+    interpreter: Interpreter,
+    locale: Locale,
+    lints: Lints,
+    error_output: Box<dyn Write>,
+}
+
+impl Lox {
+    pub fn new() -> Self {
+        Self::with_args(Vec::new())
+    }
+
+    /// An engine whose scripts see `script_args` through `args()`, `argc()`
+    /// and `arg()`. `env()` and `exit()` reach into the host process, so
+    /// they need `enable_process_natives`.
+    pub fn with_args(script_args: Vec<String>) -> Self {
+        Self {
+            interpreter: Interpreter::new(script_args),
+            locale: Locale::default(),
+            lints: Lints::default(),
+            error_output: Box::new(io::stderr()),
+        }
+    }
+
+    /// Defines `env()`, which reads the host's environment variables, and
+    /// `exit()`, which stops the script. `run_source` then returns the exit
+    /// status as a `DiagnosticKind::Exit` diagnostic, leaving the process to
+    /// the caller.
+    pub fn enable_process_natives(&mut self) {
+        self.interpreter.define_process_natives();
+    }
+
+    pub fn set_compat(&mut self, compat: Compat) {
+        self.set_locale(Locale::new(self.locale.language, compat));
+    }
+
+    pub fn set_language(&mut self, language: Language) {
+        self.set_locale(Locale::new(language, self.locale.compat));
+    }
+
+    /// Sets the level of a lint, which `// lox:` pragmas in the source can
+    /// still override.
+    pub fn set_lint_level(&mut self, lint: Lint, level: Level) {
+        self.lints.set_level(lint, level);
+    }
+
+    /// Names the file the next sources come from, which stack traces show.
+    pub fn set_file(&mut self, file: Option<&str>) {
+        self.interpreter.set_file(file.map(String::from));
+    }
+
+    /// Bounds the scripts run from now on, which fail with a runtime error
+    /// when they exceed a limit instead of crashing or hanging the process.
+    /// Once on, the sandbox stays on.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.interpreter.set_limits(limits);
+    }
+
+    fn set_locale(&mut self, locale: Locale) {
+        self.locale = locale;
+        self.interpreter.set_locale(locale);
+    }
+
+    pub fn set_output(&mut self, output: impl Write + 'static) {
+        self.interpreter.set_output(Box::new(output));
+    }
+
+    pub fn set_error_output(&mut self, error_output: impl Write + 'static) {
+        self.error_output = Box::new(error_output);
+    }
+
+    pub fn set_input(&mut self, input: impl BufRead + 'static) {
+        self.interpreter.set_input(Box::new(input));
+    }
+
+    pub fn run_source(&mut self, source: &str) -> Vec<Diagnostic> {
+        let mut error_handler = ErrorHandler::new(self.locale, self.lints);
+
+        if let Some(expr) = Lox::parse(source, &mut error_handler) {
+            self.interpreter.interpret(expr, &mut error_handler);
+        }
+
+        let diagnostics = error_handler.into_diagnostics();
+
+        for diagnostic in &diagnostics {
+            if let DiagnosticKind::Exit(_) = diagnostic.kind {
+                continue;
+            }
+
+            writeln!(self.error_output, "{}", diagnostic.render(self.locale))
+                .expect("Error writing diagnostics");
+        }
+
+        diagnostics
+    }
+
+    /// Runs `source` with `hook` paused before each expression.
+    pub(crate) fn debug(&mut self, source: &str, hook: Box<dyn Hook>) -> Vec<Diagnostic> {
+        self.interpreter.set_hook(Some(hook));
+        let diagnostics = self.run_source(source);
+        self.interpreter.set_hook(None);
+
+        diagnostics
+    }
+
+    pub fn eval_expression(&mut self, source: &str) -> Result<LiteralType, Vec<Diagnostic>> {
+        let mut error_handler = ErrorHandler::new(self.locale, self.lints);
+
+        let Some(expr) = Lox::parse(source, &mut error_handler) else {
+            return Err(error_handler.into_diagnostics());
+        };
+
+        self.interpreter.evaluate_script(expr).map_err(|err| {
+            error_handler.runtime_error(err);
+            error_handler.into_diagnostics()
+        })
+    }
+
+    /// Runs `source` under the interactive debugger, which reads its commands
+    /// from `input` and answers on `output`.
+    pub fn run_debugger(
+        &mut self,
+        source: &str,
+        input: impl BufRead + 'static,
+        output: impl Write + 'static,
+    ) -> Vec<Diagnostic> {
+        let debugger = Debugger::new(source, input, output, self.locale);
+        self.debug(source, Box::new(debugger))
+    }
+
+    /// Prints the tokens or the syntax tree of `source` instead of running
+    /// it, along with the diagnostics found on the way.
+    pub fn dump(&self, dump: Dump, source: &str) -> (String, Vec<Diagnostic>) {
+        let mut error_handler = ErrorHandler::new(self.locale, self.lints);
+        let mut output = String::new();
+
+        if dump == Dump::Tokens {
+            let mut scanner = Scanner::new(source.to_string(), &mut error_handler);
+
+            for token in scanner.scan_tokens() {
+                output.push_str(&format!("{}:{} {}\n", token.line, token.column, token));
+            }
+        } else if let Some(expr) = Lox::parse(source, &mut error_handler) {
+            let tree = match dump {
+                Dump::Ast => AstPrinter::print(expr),
+                _ => AstJson::print(expr),
+            };
+
+            output.push_str(&tree);
+            output.push('\n');
+        }
+
+        (output, error_handler.into_diagnostics())
+    }
+
+    /// Reprints `source` in the canonical layout, keeping its comments.
+    pub fn format(&self, source: &str) -> Result<String, Vec<Diagnostic>> {
+        let mut error_handler = ErrorHandler::new(self.locale, self.lints);
+
+        formatter::format(source, &mut error_handler)
+            .ok_or_else(|| error_handler.into_diagnostics())
+    }
+
+    /// Parses `source` into a concrete syntax tree that prints back as the
+    /// exact source, comments and whitespace included.
+    pub fn parse_cst(&self, source: &str) -> Result<SyntaxNode, Vec<Diagnostic>> {
+        let mut error_handler = ErrorHandler::new(self.locale, self.lints);
+
+        let mut scanner = Scanner::lossless(source.to_string(), &mut error_handler);
+        let tokens = scanner.scan_tokens().to_owned();
+        let tree = Parser::lossless(tokens, &mut error_handler).parse_cst();
+
+        match tree {
+            Some(tree) if !error_handler.had_error() => Ok(tree),
+            _ => Err(error_handler.into_diagnostics()),
+        }
+    }
+
+    /// Reports the syntax errors and lint warnings of `source` without
+    /// running it.
+    pub fn check(&self, source: &str) -> Vec<Diagnostic> {
+        let mut error_handler = ErrorHandler::new(self.locale, self.lints);
+        Lox::parse(source, &mut error_handler);
+
+        error_handler.into_diagnostics()
+    }
+
+    pub fn set_global(&mut self, name: &str, value: LiteralType) {
+        self.interpreter.define_global(name, value);
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<LiteralType> {
+        self.interpreter.get_global(name)
+    }
+
+    /// Names of every global, natives included, in alphabetical order.
+    pub fn global_names(&self) -> Vec<String> {
+        self.interpreter.global_names()
+    }
+
+    pub fn define_native<F>(&mut self, name: &str, arity: usize, function: F)
+    where
+        F: Fn(&[LiteralType]) -> Result<LiteralType, String> + 'static,
+    {
+        self.interpreter.define_native(name, arity, function);
+    }
+
+    pub(crate) fn parse(source: &str, error_handler: &mut ErrorHandler) -> Option<Expr> {
+        let mut scanner = Scanner::new(source.to_string(), error_handler);
+        let tokens = scanner.scan_tokens().to_owned();
+        let mut parser = Parser::new(tokens, error_handler);
+        let expression = parser.parse();
+
+        // Lints assume the tree means what it says, which it can't once
+        // parsing reported an error.
+        if let Some(expr) = &expression
+            && !error_handler.had_error()
+        {
+            lint::check(expr.clone(), error_handler);
+        }
+
+        if error_handler.had_error() {
+            return None;
+        }
+
+        expression
+    }
+}
+
+impl Default for Lox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        cst::{SyntaxElement, SyntaxKind},
+        error_handler::StackFrame,
+        parser::{MAX_DEPTH, MAX_NESTING},
+        token::{Trivia, TriviaKind},
+    };
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl SharedBuffer {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.borrow().clone()).unwrap()
+        }
+    }
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_globals() {
+        let mut lox = Lox::new();
+        lox.set_global("answer", LiteralType::Number(42.0));
+        lox.define_native("half", 1, |arguments| match arguments[0] {
+            LiteralType::Number(value) => Ok(LiteralType::Number(value / 2.0)),
+            _ => Err(String::from("Operand must be a number.")),
+        });
+
+        let result = lox.eval_expression("half(answer) + 1");
+
+        assert_eq!(result, Ok(LiteralType::Number(22.0)));
+        assert_eq!(lox.get_global("answer"), Some(LiteralType::Number(42.0)));
+        assert_eq!(lox.get_global("missing"), None);
+    }
+
+    #[test]
+    fn test_script_args() {
+        let mut lox = Lox::with_args(vec![String::from("a"), String::from("b c")]);
+        lox.set_error_output(io::sink());
+        lox.enable_process_natives();
+
+        let string = |text: &str| Ok(LiteralType::String(String::from(text)));
+        assert_eq!(lox.eval_expression("args()"), string("a b c"));
+        assert_eq!(lox.eval_expression("argc()"), Ok(LiteralType::Number(2.0)));
+        assert_eq!(lox.eval_expression("arg(1)"), string("b c"));
+        assert_eq!(lox.eval_expression("arg(-1)"), Ok(LiteralType::Nil));
+        assert_eq!(lox.eval_expression("arg(2)"), Ok(LiteralType::Nil));
+
+        for (source, code) in [
+            ("arg(0.5)", "L0319"),
+            ("exit(1.5)", "L0309"),
+            ("exit(256)", "L0309"),
+            ("exit(-1)", "L0309"),
+        ] {
+            let diagnostics = lox.eval_expression(source).unwrap_err();
+            assert_eq!(diagnostics[0].code, Some(code), "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_process_natives() {
+        let mut lox = Lox::new();
+        let errors = SharedBuffer::default();
+        lox.set_error_output(errors.clone());
+
+        let undefined = lox.run_source("env(\"HOME\")");
+        assert_eq!(undefined[0].code, Some("L0304"));
+
+        lox.enable_process_natives();
+        let reported = errors.contents();
+        let exited = lox.run_source("exit(3)");
+
+        assert_eq!(exited.len(), 1);
+        assert_eq!(exited[0].kind, DiagnosticKind::Exit(3));
+        assert_eq!(errors.contents(), reported);
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let mut lox = Lox::new();
+        lox.set_error_output(io::sink());
+
+        let syntax = lox.run_source("(1 + 2");
+        let runtime = lox.eval_expression("\n-\"a\"").unwrap_err();
+
+        assert_eq!(syntax.len(), 1);
+        assert_eq!(syntax[0].kind, DiagnosticKind::Syntax);
+        assert_eq!(syntax[0].message, "Expect ')' after expression.");
+        assert_eq!(runtime[0].kind, DiagnosticKind::Runtime);
+        assert_eq!(runtime[0].line, 2);
+    }
+
+    #[test]
+    fn test_redirected_output() {
+        let output = SharedBuffer::default();
+        let error_output = SharedBuffer::default();
+
+        let mut lox = Lox::new();
+        lox.set_output(output.clone());
+        lox.set_error_output(error_output.clone());
+
+        lox.run_source("1 + 2");
+        lox.run_source("clock");
+        lox.run_source("-nil");
+
+        assert_eq!(output.contents(), "Number(3.0)\n<native fn>\n");
+        assert_eq!(
+            error_output.contents(),
+            "Error[L0301]: Operand must be a number \n[line 1] in script\n"
+        );
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let mut lox = Lox::new();
+        lox.set_error_output(io::sink());
+        lox.set_limits(Limits {
+            max_depth: 3,
+            ..Limits::default()
+        });
+
+        let diagnostics = lox.eval_expression("\n-(-(-(-1)))").unwrap_err();
+
+        assert_eq!(diagnostics[0].code, Some("L0311"));
+        assert_eq!(diagnostics[0].message, "Stack overflow.");
+        assert_eq!(diagnostics[0].line, 2);
+
+        lox.set_limits(Limits {
+            max_depth: 8,
+            ..Limits::default()
+        });
+        assert_eq!(
+            lox.eval_expression("-(-(-(-1)))"),
+            Ok(LiteralType::Number(1.0))
+        );
+    }
+
+    #[test]
+    fn test_lambda_stack_trace() {
+        let mut lox = Lox::new();
+        lox.set_error_output(io::sink());
+        lox.set_limits(Limits {
+            max_depth: 32,
+            ..Limits::default()
+        });
+
+        let diagnostics = lox.eval_expression("((n) =>\n  -n\n)(\"a\")").unwrap_err();
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "Error[L0301]: Operand must be a number \n[line 2] in <lambda>()\n[line 3] in script"
+        );
+
+        let diagnostics = lox
+            .eval_expression("((f) => f(f))((f) => f(f))")
+            .unwrap_err();
+        assert_eq!(diagnostics[0].code, Some("L0311"));
+        assert!(diagnostics[0].stack.len() > 10);
+    }
+
+    #[test]
+    fn test_sandbox() {
+        let mut lox = Lox::new();
+        lox.set_error_output(io::sink());
+        lox.set_limits(Limits {
+            sandbox: true,
+            ..Limits::default()
+        });
+
+        for source in ["args()", "env(\"HOME\")", "exit(0)"] {
+            let diagnostics = lox.eval_expression(source).unwrap_err();
+            assert_eq!(diagnostics[0].code, Some("L0315"));
+        }
+        assert_eq!(lox.eval_expression("1 + 1"), Ok(LiteralType::Number(2.0)));
+
+        // Neither later limits nor the process natives lift it.
+        lox.set_limits(Limits::default());
+        lox.enable_process_natives();
+        for source in ["args()", "env(\"HOME\")", "exit(0)"] {
+            let diagnostics = lox.eval_expression(source).unwrap_err();
+            assert_eq!(diagnostics[0].code, Some("L0315"));
+        }
+    }
+
+    #[test]
+    fn test_jlox_natives() {
+        let mut lox = Lox::with_args(vec![String::from("a")]);
+        lox.set_error_output(io::sink());
+        lox.enable_process_natives();
+        lox.set_compat(Compat::Jlox);
+        lox.enable_process_natives();
+        lox.set_limits(Limits {
+            sandbox: true,
+            ..Limits::default()
+        });
+
+        for name in ["args", "argc", "arg", "env", "exit"] {
+            assert_eq!(lox.get_global(name), None, "{}", name);
+        }
+        assert!(lox.get_global("clock").is_some());
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        let mut lox = Lox::new();
+        lox.set_error_output(io::sink());
+        let nested = |depth| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+
+        assert_eq!(
+            lox.eval_expression(&nested(MAX_NESTING)),
+            Ok(LiteralType::Number(1.0))
+        );
+        assert_eq!(
+            lox.eval_expression(&"-".repeat(MAX_NESTING)).unwrap_err()[0].code,
+            Some("L0101")
+        );
+
+        for source in [
+            nested(MAX_NESTING + 1),
+            "!".repeat(100_000) + "true",
+            "f(".repeat(MAX_NESTING + 1),
+            vec!["1"; MAX_DEPTH + 2].join(" + "),
+        ] {
+            let diagnostics = lox.eval_expression(&source).unwrap_err();
+            assert_eq!(diagnostics[0].code, Some("L0106"));
+        }
+    }
+
+    #[test]
+    fn test_stack_trace() {
+        let mut lox = Lox::new();
+        lox.set_output(io::sink());
+        lox.set_error_output(io::sink());
+        lox.define_native("fail", 0, |_| Err(String::from("Failed.")));
+        lox.set_file(Some("main.lox"));
+
+        let diagnostics = lox.run_source("1 +\n  fail()");
+
+        assert_eq!(
+            diagnostics[0].stack,
+            [
+                StackFrame {
+                    function: Some(String::from("fail")),
+                    file: None,
+                    line: 2,
+                },
+                StackFrame {
+                    function: None,
+                    file: Some(String::from("main.lox")),
+                    line: 2,
+                },
+            ]
+        );
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "Failed. \n[line 2] in fail()\n[line 2] in script"
+        );
+
+        let diagnostics = lox.run_source("(() =>\n  fail()\n)()");
+
+        assert_eq!(
+            diagnostics[0].stack,
+            [
+                StackFrame {
+                    function: Some(String::from("fail")),
+                    file: None,
+                    line: 2,
+                },
+                StackFrame {
+                    function: Some(String::from("<lambda>")),
+                    file: Some(String::from("main.lox")),
+                    line: 2,
+                },
+                StackFrame {
+                    function: None,
+                    file: Some(String::from("main.lox")),
+                    line: 3,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lints() {
+        let mut lox = Lox::new();
+        lox.set_output(io::sink());
+        lox.set_error_output(io::sink());
+
+        let warned = lox.run_source("clock == clock");
+        let allowed = lox.run_source("// lox: allow(self_comparison)\nclock == clock");
+        lox.set_lint_level(Lint::SelfComparison, Level::Deny);
+        let denied = lox.run_source("clock != clock");
+        let unknown = lox.run_source("// lox: allow(missing)\n1");
+
+        assert_eq!(warned[0].kind, DiagnosticKind::Warning);
+        assert_eq!(warned[0].message, "Comparing 'clock' with itself.");
+        assert!(allowed.is_empty());
+        assert_eq!(denied[0].kind, DiagnosticKind::Syntax);
+        assert_eq!(unknown[0].message, "Unknown lint 'missing'.");
+
+        let unused = lox.run_source("(a, b, _c) => (d) => a + d");
+        let read = lox.run_source("(a, b) => a++ + (b += 1)");
+        let shadowed = lox.run_source("(a) => (b) => (a) => a + b");
+
+        let messages = |diagnostics: &[Diagnostic]| {
+            diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.message.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(messages(&unused), ["Parameter 'b' is never used."]);
+        assert!(read.is_empty());
+        assert_eq!(
+            messages(&shadowed),
+            [
+                "Parameter 'a' shadows a parameter of an enclosing function.",
+                "Parameter 'a' is never used."
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_cst() {
+        let source = "#!/usr/bin/env rlox\n// c\n( 1+2 ) /* b */ // t\n";
+        let tree = Lox::new().parse_cst(source).unwrap();
+        let tokens = tree.tokens();
+        let kinds = |trivia: &[Trivia]| trivia.iter().map(|t| t.kind).collect::<Vec<_>>();
+
+        assert_eq!(tree.to_string(), source);
+        assert!(matches!(
+            &tree.children[0],
+            SyntaxElement::Node(SyntaxNode {
+                kind: SyntaxKind::Grouping,
+                ..
+            })
+        ));
+        assert_eq!(
+            kinds(&tokens[0].leading_trivia),
+            [
+                TriviaKind::Shebang,
+                TriviaKind::Newline,
+                TriviaKind::LineComment,
+                TriviaKind::Newline
+            ]
+        );
+        assert_eq!(
+            kinds(&tokens[4].trailing_trivia),
+            [
+                TriviaKind::Whitespace,
+                TriviaKind::BlockComment,
+                TriviaKind::Whitespace,
+                TriviaKind::LineComment
+            ]
+        );
+        assert_eq!(kinds(&tokens[5].leading_trivia), [TriviaKind::Newline]);
+
+        let SyntaxElement::Node(binary) = &Lox::new().parse_cst("1+2*3").unwrap().children[0]
+        else {
+            panic!("expected a node");
+        };
+        let kinds = binary
+            .children
+            .iter()
+            .map(|child| match child {
+                SyntaxElement::Node(node) => format!("{:?}", node.kind),
+                SyntaxElement::Token(token) => token.lexeme.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(kinds, ["Literal", "+", "Binary"]);
+
+        let unary = Lox::new().parse_cst("-1").unwrap();
+        assert!(matches!(
+            &unary.children[0],
+            SyntaxElement::Node(SyntaxNode {
+                kind: SyntaxKind::Unary,
+                children,
+            }) if children.len() == 2
+        ));
+
+        let lambda = Lox::new().parse_cst("(a, b) => a").unwrap();
+        assert!(matches!(
+            &lambda.children[0],
+            SyntaxElement::Node(SyntaxNode {
+                kind: SyntaxKind::Lambda,
+                children,
+            }) if children.len() == 7
+        ));
+
+        let increment = Lox::new().parse_cst("x++").unwrap();
+        assert!(matches!(
+            &increment.children[0],
+            SyntaxElement::Node(SyntaxNode {
+                kind: SyntaxKind::Increment,
+                children,
+            }) if children.len() == 2
+        ));
+    }
+
+    #[test]
+    fn test_language() {
+        let mut lox = Lox::new();
+        lox.set_error_output(io::sink());
+        lox.set_language(Language::PtBr);
+
+        let syntax = lox.run_source("(1 + 2");
+        let runtime = lox.eval_expression("missing").unwrap_err();
+
+        assert_eq!(syntax[0].message, "Esperado ')' após a expressão.");
+        assert_eq!(runtime[0].message, "Variável 'missing' não definida.");
+
+        let locale = Locale::new(Language::PtBr, Compat::Rlox);
+        assert_eq!(
+            syntax[0].render(locale),
+            "[line: 1] Erro[L0102] no fim: Esperado ')' após a expressão."
+        );
+        assert_eq!(
+            runtime[0].render(locale),
+            "Erro[L0304]: Variável 'missing' não definida. \n[line 1] no script"
+        );
+        assert_eq!(
+            lox.check("x == x")[0].render(locale),
+            "[line: 1] Aviso[L0401]  em'==': Comparando 'x' com ele mesmo."
+        );
+    }
+}
@@ -0,0 +1,502 @@
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+};
+
+use serde_json::{Value, json};
+
+use crate::{
+    error_handler::{Diagnostic, DiagnosticKind, ErrorHandler, Location},
+    expr::{Call, Comma, Expr, Lambda, Ternary, Visitor},
+    lint::Lints,
+    lox::Lox,
+    messages::Locale,
+    parser::Parser,
+    protocol::{self, read_message},
+    scanner::{KEYWORDS, Scanner},
+    token::{LiteralType, Token},
+    token_type::TokenType,
+};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+
+const SEVERITY_ERROR: u8 = 1;
+const SEVERITY_WARNING: u8 = 2;
+
+const COMPLETION_FUNCTION: u8 = 3;
+const COMPLETION_VARIABLE: u8 = 6;
+const COMPLETION_KEYWORD: u8 = 14;
+
+/// Serves the Language Server Protocol on `input` and `output` until the
+/// client sends `exit`, and returns the exit code the protocol asks for.
+///
+/// The only declarations are lambda parameters, so a name either resolves to
+/// a parameter of an enclosing lambda or is a global. Globals have no
+/// definition in the document, and documents have no symbols.
+pub fn serve(lox: &Lox, mut input: impl BufRead, output: impl Write) -> io::Result<i32> {
+    let mut server = Server {
+        lox,
+        documents: HashMap::new(),
+        output,
+        shut_down: false,
+    };
+
+    while let Some(body) = read_message(&mut input)? {
+        let message: Value = match serde_json::from_slice(&body) {
+            Ok(message) => message,
+            Err(err) => {
+                server.respond(&Value::Null, Err((PARSE_ERROR, err.to_string())))?;
+                continue;
+            }
+        };
+
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+
+        match message.get("id") {
+            Some(id) => {
+                let result = server.request(method, params);
+                server.respond(id, result)?;
+            }
+            None if method == "exit" => return Ok(if server.shut_down { 0 } else { 1 }),
+            None => server.notification(method, params)?,
+        }
+    }
+
+    Ok(1)
+}
+
+struct Server<'a, W> {
+    lox: &'a Lox,
+    documents: HashMap<String, Document>,
+    output: W,
+    shut_down: bool,
+}
+
+impl<W: Write> Server<'_, W> {
+    fn request(&mut self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        if self.shut_down {
+            return Err((INVALID_REQUEST, String::from("The server is shut down.")));
+        }
+
+        match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": 1,
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "referencesProvider": true,
+                    "documentSymbolProvider": true,
+                    "completionProvider": {},
+                },
+                "serverInfo": {
+                    "name": "rlox",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            })),
+            "shutdown" => {
+                self.shut_down = true;
+                Ok(Value::Null)
+            }
+            "textDocument/hover" => Ok(self.hover(params)),
+            "textDocument/definition" => Ok(self.definition(params)),
+            "textDocument/references" => Ok(self.references(params)),
+            "textDocument/documentSymbol" => Ok(json!([])),
+            "textDocument/completion" => Ok(self.completion(params)),
+            _ => Err((METHOD_NOT_FOUND, format!("Unknown method '{}'.", method))),
+        }
+    }
+
+    fn notification(&mut self, method: &str, params: &Value) -> io::Result<()> {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+
+        let text = match method {
+            "textDocument/didOpen" => params["textDocument"]["text"].as_str(),
+            // Only full syncs are advertised, so the last change is the text.
+            "textDocument/didChange" => params["contentChanges"]
+                .as_array()
+                .and_then(|changes| changes.last())
+                .and_then(|change| change["text"].as_str()),
+            "textDocument/didClose" => {
+                self.documents.remove(uri);
+                return self.publish_diagnostics(uri, Vec::new());
+            }
+            _ => return Ok(()),
+        };
+
+        let Some(text) = text else {
+            return Ok(());
+        };
+
+        let document = Document::new(text.to_string());
+        let diagnostics = self
+            .lox
+            .check(&document.text)
+            .iter()
+            .map(|diagnostic| document.diagnostic(diagnostic))
+            .collect();
+
+        self.documents.insert(uri.to_string(), document);
+        self.publish_diagnostics(uri, diagnostics)
+    }
+
+    fn hover(&self, params: &Value) -> Value {
+        let Some((document, token)) = self.token_at(params) else {
+            return Value::Null;
+        };
+
+        let name = &token.lexeme;
+        let description = match self.lox.get_global(name) {
+            _ if document.parameter(token).is_some() => format!("(parameter) {}", name),
+            Some(LiteralType::NativeFunction(function)) => {
+                format!("(native function) {}, arity {}", name, function.arity)
+            }
+            Some(_) => format!("(global) {}", name),
+            None => format!("(undefined) {}", name),
+        };
+
+        json!({
+            "contents": {
+                "kind": "plaintext",
+                "value": description,
+            },
+        })
+    }
+
+    fn definition(&self, params: &Value) -> Value {
+        let Some((document, token)) = self.token_at(params) else {
+            return Value::Null;
+        };
+
+        match document.parameter(token) {
+            Some(parameter) => json!({
+                "uri": params["textDocument"]["uri"],
+                "range": document.token_range(parameter),
+            }),
+            None => Value::Null,
+        }
+    }
+
+    fn references(&self, params: &Value) -> Value {
+        let Some((document, token)) = self.token_at(params) else {
+            return Value::Null;
+        };
+
+        let uri = &params["textDocument"]["uri"];
+        let parameter = document.parameter(token).map(position);
+
+        document
+            .identifiers()
+            .filter(|other| {
+                other.lexeme == token.lexeme && document.parameter(other).map(position) == parameter
+            })
+            .map(|other| json!({ "uri": uri, "range": document.token_range(other) }))
+            .collect()
+    }
+
+    fn completion(&self, params: &Value) -> Value {
+        let mut items: Vec<Value> = KEYWORDS
+            .iter()
+            .map(|keyword| json!({ "label": keyword, "kind": COMPLETION_KEYWORD }))
+            .collect();
+
+        let mut names = self.lox.global_names();
+        if let Some(document) = self.document(params) {
+            names.extend(document.identifiers().map(|token| token.lexeme.clone()));
+        }
+        names.sort();
+        names.dedup();
+
+        items.extend(names.into_iter().map(|name| {
+            let kind = match self.lox.get_global(&name) {
+                Some(LiteralType::NativeFunction(_)) => COMPLETION_FUNCTION,
+                _ => COMPLETION_VARIABLE,
+            };
+
+            json!({ "label": name, "kind": kind })
+        }));
+
+        Value::Array(items)
+    }
+
+    fn document(&self, params: &Value) -> Option<&Document> {
+        let uri = params["textDocument"]["uri"].as_str()?;
+        self.documents.get(uri)
+    }
+
+    /// The identifier under the cursor of a text document position request.
+    fn token_at(&self, params: &Value) -> Option<(&Document, &Token)> {
+        let document = self.document(params)?;
+        let offset = document.offset(&params["position"])?;
+
+        document
+            .identifiers()
+            .find(|token| {
+                let (start, end) = document.token_span(token);
+                (start..=end).contains(&offset)
+            })
+            .map(|token| (document, token))
+    }
+
+    fn publish_diagnostics(&mut self, uri: &str, diagnostics: Vec<Value>) -> io::Result<()> {
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics },
+        }))
+    }
+
+    fn respond(&mut self, id: &Value, result: Result<Value, (i64, String)>) -> io::Result<()> {
+        let response = match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": message },
+            }),
+        };
+
+        self.send(response)
+    }
+
+    fn send(&mut self, message: Value) -> io::Result<()> {
+        protocol::write_message(&mut self.output, &message)
+    }
+}
+
+/// An open text document. Tokens carry byte columns, while the protocol
+/// counts UTF-16 code units, so positions are converted through offsets.
+struct Document {
+    text: String,
+    tokens: Vec<Token>,
+    /// The parameter each name inside a lambda resolves to, keyed by the
+    /// position of the name. Names missing from it are globals.
+    parameters: HashMap<(usize, usize), Token>,
+}
+
+impl Document {
+    fn new(text: String) -> Self {
+        // The diagnostics come from `Lox::check`; these tokens and the tree
+        // only locate names. A document that doesn't parse has only globals.
+        let mut error_handler = ErrorHandler::new(Locale::default(), Lints::default());
+        let tokens = Scanner::new(text.clone(), &mut error_handler)
+            .scan_tokens()
+            .to_owned();
+
+        let mut resolver = Resolver::default();
+        if let Some(expr) = Parser::new(tokens.clone(), &mut error_handler).parse() {
+            resolver.visit(expr);
+        }
+
+        Self {
+            text,
+            tokens,
+            parameters: resolver.parameters,
+        }
+    }
+
+    /// The parameter that `name` resolves to, if it isn't a global.
+    fn parameter(&self, name: &Token) -> Option<&Token> {
+        self.parameters.get(&position(name))
+    }
+
+    fn identifiers(&self) -> impl Iterator<Item = &Token> {
+        self.tokens
+            .iter()
+            .filter(|token| token.token_type == TokenType::Identifier)
+    }
+
+    fn diagnostic(&self, diagnostic: &Diagnostic) -> Value {
+        let line_start = self.line_start(diagnostic.line.saturating_sub(1));
+        let line_end = self.text[line_start..]
+            .find('\n')
+            .map_or(self.text.len(), |end| line_start + end);
+
+        let (start, end) = match (diagnostic.column, &diagnostic.location) {
+            (Some(column), Location::Token(lexeme)) => {
+                let start = line_start + column - 1;
+                (start, start + lexeme.len())
+            }
+            (Some(column), _) => (line_start + column - 1, line_start + column - 1),
+            (None, _) => (line_start, line_end),
+        };
+
+        let severity = match diagnostic.kind {
+            DiagnosticKind::Warning => SEVERITY_WARNING,
+            DiagnosticKind::Syntax | DiagnosticKind::Runtime | DiagnosticKind::Exit(_) => {
+                SEVERITY_ERROR
+            }
+        };
+
+        json!({
+            "range": self.range(start, end),
+            "severity": severity,
+            "code": diagnostic.code,
+            "source": "rlox",
+            "message": diagnostic.message,
+        })
+    }
+
+    /// Byte offsets of the start and end of `token`. A token's line is the
+    /// one it ends on, but its column is where it starts.
+    fn token_span(&self, token: &Token) -> (usize, usize) {
+        let first_line = token.line - 1 - token.lexeme.matches('\n').count();
+        let start = self.line_start(first_line) + token.column - 1;
+
+        (start, start + token.lexeme.len())
+    }
+
+    fn token_range(&self, token: &Token) -> Value {
+        let (start, end) = self.token_span(token);
+        self.range(start, end)
+    }
+
+    fn range(&self, start: usize, end: usize) -> Value {
+        json!({ "start": self.position(start), "end": self.position(end) })
+    }
+
+    fn line_start(&self, line: usize) -> usize {
+        self.text
+            .split_inclusive('\n')
+            .take(line)
+            .map(str::len)
+            .sum()
+    }
+
+    fn position(&self, offset: usize) -> Value {
+        let mut offset = offset.min(self.text.len());
+        while !self.text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let before = &self.text[..offset];
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+
+        json!({
+            "line": before.matches('\n').count(),
+            "character": before[line_start..].encode_utf16().count(),
+        })
+    }
+
+    fn offset(&self, position: &Value) -> Option<usize> {
+        let line = position["line"].as_u64()? as usize;
+        let character = position["character"].as_u64()? as usize;
+
+        let line_start = self.line_start(line);
+        let mut units = 0;
+
+        for (index, c) in self.text[line_start..].char_indices() {
+            if units >= character || c == '\n' {
+                return Some(line_start + index);
+            }
+            units += c.len_utf16();
+        }
+
+        Some(self.text.len())
+    }
+}
+
+fn position(token: &Token) -> (usize, usize) {
+    (token.line, token.column)
+}
+
+/// Resolves every name in a tree to the parameter of the innermost enclosing
+/// lambda that declares it.
+#[derive(Default)]
+struct Resolver {
+    scopes: Vec<Vec<Token>>,
+    parameters: HashMap<(usize, usize), Token>,
+}
+
+impl Resolver {
+    fn resolve(&mut self, name: &Token) {
+        let parameter = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.iter().find(|param| param.lexeme == name.lexeme));
+
+        if let Some(parameter) = parameter {
+            self.parameters.insert(position(name), parameter.clone());
+        }
+    }
+}
+
+impl Visitor<()> for Resolver {
+    fn visit(&mut self, expr: Expr) {
+        match expr {
+            Expr::Lambda(lambda) => {
+                let Lambda { params, body, .. } = *lambda;
+                for param in &params {
+                    self.parameters.insert(position(param), param.clone());
+                }
+
+                self.scopes.push(params);
+                self.visit(*body);
+                self.scopes.pop();
+            }
+            Expr::Variable(variable) => self.resolve(&variable.name),
+            Expr::Increment(increment) => self.resolve(&increment.name),
+            Expr::Assign(assign) => {
+                self.resolve(&assign.name);
+                self.visit(*assign.value);
+            }
+            Expr::Binary(binary) => {
+                self.visit(*binary.left);
+                self.visit(*binary.right);
+            }
+            Expr::Grouping(grouping) => self.visit(*grouping.expression),
+            Expr::Unary(unary) => self.visit(*unary.right),
+            Expr::Comma(comma) => {
+                let Comma { left, right } = *comma;
+                self.visit(*left);
+                self.visit(*right);
+            }
+            Expr::Ternary(ternary) => {
+                let Ternary {
+                    condition,
+                    then_branch,
+                    else_branch,
+                } = *ternary;
+                self.visit(*condition);
+                self.visit(*then_branch);
+                self.visit(*else_branch);
+            }
+            Expr::Call(call) => {
+                let Call {
+                    callee, arguments, ..
+                } = *call;
+                self.visit(*callee);
+                arguments
+                    .into_iter()
+                    .for_each(|argument| self.visit(argument));
+            }
+            Expr::Literal(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_positions() {
+        let document = Document::new(String::from("\"é\" + x\nlen(\"ü\", x)"));
+        let x = document.identifiers().nth(2).unwrap();
+
+        assert_eq!(
+            document.token_range(x),
+            json!({
+                "start": { "line": 1, "character": 9 },
+                "end": { "line": 1, "character": 10 },
+            })
+        );
+        assert_eq!(
+            document.offset(&json!({ "line": 0, "character": 6 })),
+            Some(7)
+        );
+    }
+}
@@ -1,35 +1,215 @@
-use clap::Parser;
-use program::Program;
+use std::{panic, process, thread, time::Duration};
+
+use clap::{Parser, Subcommand};
+use interpreter::{
+    Compat, DEFAULT_MAX_DEPTH, Dump, Language, Level, Limits, Lint, Lints, MAX_MAX_DEPTH,
+};
+use program::{Input, Program};
 
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, args_conflicts_with_subcommands = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     #[arg(short, long)]
     generate: Option<String>,
 
-    script: Option<String>,
+    /// Print the scanned tokens and stop before parsing
+    #[arg(long, group = "dump")]
+    tokens: bool,
+
+    /// Print the parenthesized AST and stop before execution
+    #[arg(long, group = "dump")]
+    ast: bool,
+
+    /// Print the AST as JSON and stop before execution
+    #[arg(long, group = "dump")]
+    ast_json: bool,
+
+    /// Match the diagnostics and output of another Lox implementation
+    #[arg(long, value_enum, default_value_t = Compat::Rlox)]
+    compat: Compat,
+
+    /// Language of the diagnostics, taken from `LANG` when omitted
+    #[arg(long, value_enum)]
+    lang: Option<Language>,
+
+    /// Allow a lint
+    #[arg(short = 'A', long = "allow", value_enum, value_name = "LINT")]
+    allowed: Vec<Lint>,
+
+    /// Report a lint as a warning
+    #[arg(short = 'W', long = "warn", value_enum, value_name = "LINT")]
+    warned: Vec<Lint>,
+
+    /// Report a lint as an error, which wins over -A and -W
+    #[arg(short = 'D', long = "deny", value_enum, value_name = "LINT")]
+    denied: Vec<Lint>,
+
+    /// Describe an error code such as L0102 and exit
+    #[arg(long, value_name = "CODE")]
+    explain: Option<String>,
+
+    /// How deeply evaluation can recurse before failing with a stack overflow
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_DEPTH, value_parser = depth)]
+    max_depth: usize,
+
+    /// How many expressions a script can evaluate
+    #[arg(long, value_name = "N")]
+    max_steps: Option<u64>,
+
+    /// How many seconds a script can run
+    #[arg(long, value_name = "SECONDS", value_parser = seconds)]
+    timeout: Option<Duration>,
+
+    /// How many bytes of strings a script can build
+    #[arg(long, value_name = "BYTES")]
+    max_heap: Option<usize>,
+
+    /// Make args(), argc(), arg(), env() and exit() fail, for running code that isn't trusted
+    #[arg(long)]
+    sandbox: bool,
+
+    /// Run the given code instead of a script
+    #[arg(short, long, allow_hyphen_values = true)]
+    eval: Option<String>,
+
+    /// Path to the script, or `-` to read it from stdin, then the arguments
+    /// passed to it. Options after the path are the script's, not rlox's.
+    #[arg(
+        value_name = "SCRIPT",
+        trailing_var_arg = true,
+        allow_hyphen_values = true
+    )]
+    script: Vec<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Format scripts in place
+    Fmt {
+        /// List the scripts that aren't formatted instead of rewriting them
+        #[arg(long)]
+        check: bool,
+
+        /// Scripts to format, or `-` to format stdin to stdout
+        #[arg(required = true)]
+        paths: Vec<String>,
+    },
+    /// Serve the Language Server Protocol on stdin and stdout
+    Lsp,
+    /// Run a script under an interactive debugger
+    Debug {
+        /// Path to the script
+        script: String,
+
+        /// Arguments passed to the script
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Serve the Debug Adapter Protocol on stdin and stdout
+    Dap,
 }
 
-mod ast_printer;
-mod error_handler;
-mod expr;
-mod interpreter;
-mod parser;
 mod program;
-mod scanner;
-mod token;
-mod token_type;
 mod tool;
 
+fn seconds(text: &str) -> Result<Duration, String> {
+    let seconds: f64 = text.parse().map_err(|err| format!("{}", err))?;
+    Duration::try_from_secs_f64(seconds).map_err(|err| format!("{}", err))
+}
+
+fn depth(text: &str) -> Result<usize, String> {
+    let depth: usize = text.parse().map_err(|err| format!("{}", err))?;
+
+    if depth > MAX_MAX_DEPTH {
+        return Err(format!("must be at most {}", MAX_MAX_DEPTH));
+    }
+
+    Ok(depth)
+}
+
 fn main() {
     let args = Args::parse();
 
-    if args.generate.is_some() {
-        tool::generate_ast::generate_ast(&args.generate.unwrap()).expect("Error generating AST");
+    if let Some(output_dir) = args.generate {
+        tool::generate_ast::generate_ast(&output_dir).expect("Error generating AST");
         return;
     }
 
-    let mut interpreter = Program::new();
+    let language = args.lang.unwrap_or_else(Language::from_env);
 
-    interpreter.init(args.script);
+    if let Some(code) = args.explain {
+        match interpreter::explain(&code, language) {
+            Ok(text) => print!("{}", text),
+            Err(message) => {
+                eprintln!("{}", message);
+                process::exit(64);
+            }
+        }
+        return;
+    }
+
+    let dump = if args.tokens {
+        Some(Dump::Tokens)
+    } else if args.ast {
+        Some(Dump::Ast)
+    } else if args.ast_json {
+        Some(Dump::AstJson)
+    } else {
+        None
+    };
+
+    let mut script = args.script.into_iter();
+    let (input, script_args) = match (args.eval, script.next(), &args.command) {
+        // The debugger runs its own script, so the input is unused.
+        (_, _, Some(Command::Debug { args, .. })) => (Input::Prompt, args.clone()),
+        (Some(code), first, _) => (Input::Eval(code), first.into_iter().chain(script).collect()),
+        (None, Some(path), _) if path == "-" => (Input::Stdin, script.collect()),
+        (None, Some(path), _) => (Input::File(path), script.collect()),
+        (None, None, _) => (Input::Prompt, Vec::new()),
+    };
+
+    let mut lints = Lints::default();
+    for (names, level) in [
+        (args.allowed, Level::Allow),
+        (args.warned, Level::Warn),
+        (args.denied, Level::Deny),
+    ] {
+        for lint in names {
+            lints.set_level(lint, level);
+        }
+    }
+
+    let limits = Limits {
+        max_depth: args.max_depth,
+        max_steps: args.max_steps,
+        timeout: args.timeout,
+        max_heap: args.max_heap,
+        sandbox: args.sandbox,
+    };
+    let command = args.command;
+    let compat = args.compat;
+
+    // Evaluation recurses on the native stack, so scripts run on a thread
+    // with room for as deep as `--max-depth` lets them go.
+    let program = thread::Builder::new()
+        .stack_size(limits.stack_size())
+        .spawn(move || {
+            let mut interpreter = Program::new(dump, script_args, compat, language, lints, limits);
+
+            match command {
+                Some(Command::Fmt { check, paths }) => interpreter.format(&paths, check),
+                Some(Command::Lsp) => interpreter.lsp(),
+                Some(Command::Debug { script, .. }) => interpreter.debug(&script),
+                Some(Command::Dap) => interpreter.dap(),
+                None => interpreter.init(input),
+            }
+        })
+        .expect("Error spawning the interpreter thread");
+
+    if let Err(payload) = program.join() {
+        panic::resume_unwind(payload);
+    }
 }
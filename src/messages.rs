@@ -0,0 +1,1493 @@
+use std::env;
+
+use clap::ValueEnum;
+
+use crate::compat::Compat;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+pub enum Language {
+    #[default]
+    En,
+    PtBr,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::En, Language::PtBr];
+
+    /// Picks the language from a POSIX locale such as `pt_BR.UTF-8`.
+    pub fn from_locale(locale: &str) -> Self {
+        if locale.to_lowercase().starts_with("pt") {
+            Language::PtBr
+        } else {
+            Language::En
+        }
+    }
+
+    pub fn from_env() -> Self {
+        env::var("LANG")
+            .map(|locale| Language::from_locale(&locale))
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum Message {
+    UnexpectedCharacter,
+    UnterminatedString,
+    UnterminatedComment,
+    ExpectColonAfterThenBranch,
+    ExpectRightParenAfterExpression,
+    ExpectExpression,
+    TooManyArguments,
+    ExpectRightParenAfterArguments,
+    TooMuchNesting,
+    OutsideLoop,
+    TooManyParameters,
+    DuplicateParameter,
+    InvalidAssignmentTarget,
+    ExpectEndOfExpression,
+    ExpectLeftParenAfterFun,
+    ExpectParameterName,
+    ExpectRightParenAfterParameters,
+    ExpectLeftBraceBeforeBody,
+    ExpectRightBraceAfterBody,
+    OperandMustBeNumber,
+    OperandsMustBeNumbers,
+    OperandsMustBeNumbersOrStrings,
+    UndefinedVariable,
+    NotCallable,
+    WrongArity,
+    Interrupted,
+    EnvironmentNameMustBeString,
+    ExitCodeMustBeInteger,
+    ClockUnavailable,
+    StackOverflow,
+    StepLimitExceeded,
+    TimeLimitExceeded,
+    HeapLimitExceeded,
+    Sandboxed,
+    OperandMustBeInteger,
+    OperandsMustBeIntegers,
+    ShiftOutOfRange,
+    ArgumentIndexMustBeInteger,
+    FileReadError,
+    StdinReadError,
+    LineReadError,
+    UnknownErrorCode,
+    SelfComparison,
+    UnknownLint,
+    UnusedParameter,
+    ShadowedParameter,
+    NotFormatted,
+    FileWriteError,
+    WrongExample,
+    RightExample,
+    BreakpointList,
+    SetUsage,
+    UnknownDebuggerCommand,
+    StoppedByDebugger,
+    ExpectLineNumber,
+    BreakpointSet,
+    BreakpointRemoved,
+    NoBreakpoint,
+    NoScriptLaunched,
+    NeedsPausedScript,
+    UnknownRequest,
+    LaunchNeedsProgram,
+    ProgramReadError,
+    GlobalsScope,
+    LocalsScope,
+    NotAVariableName,
+    DebuggerHelp,
+    ErrorSeverity,
+    WarningSeverity,
+    AtEnd,
+    AtToken,
+    InScript,
+    InFunction,
+}
+
+impl Message {
+    pub const ALL: [Message; 73] = [
+        Message::UnexpectedCharacter,
+        Message::UnterminatedString,
+        Message::UnterminatedComment,
+        Message::ExpectColonAfterThenBranch,
+        Message::ExpectRightParenAfterExpression,
+        Message::ExpectExpression,
+        Message::TooManyArguments,
+        Message::ExpectRightParenAfterArguments,
+        Message::TooMuchNesting,
+        Message::OutsideLoop,
+        Message::TooManyParameters,
+        Message::DuplicateParameter,
+        Message::InvalidAssignmentTarget,
+        Message::ExpectEndOfExpression,
+        Message::ExpectLeftParenAfterFun,
+        Message::ExpectParameterName,
+        Message::ExpectRightParenAfterParameters,
+        Message::ExpectLeftBraceBeforeBody,
+        Message::ExpectRightBraceAfterBody,
+        Message::OperandMustBeNumber,
+        Message::OperandsMustBeNumbers,
+        Message::OperandsMustBeNumbersOrStrings,
+        Message::UndefinedVariable,
+        Message::NotCallable,
+        Message::WrongArity,
+        Message::Interrupted,
+        Message::EnvironmentNameMustBeString,
+        Message::ExitCodeMustBeInteger,
+        Message::ClockUnavailable,
+        Message::StackOverflow,
+        Message::StepLimitExceeded,
+        Message::TimeLimitExceeded,
+        Message::HeapLimitExceeded,
+        Message::Sandboxed,
+        Message::OperandMustBeInteger,
+        Message::OperandsMustBeIntegers,
+        Message::ShiftOutOfRange,
+        Message::ArgumentIndexMustBeInteger,
+        Message::FileReadError,
+        Message::StdinReadError,
+        Message::LineReadError,
+        Message::UnknownErrorCode,
+        Message::SelfComparison,
+        Message::UnknownLint,
+        Message::UnusedParameter,
+        Message::ShadowedParameter,
+        Message::NotFormatted,
+        Message::FileWriteError,
+        Message::WrongExample,
+        Message::RightExample,
+        Message::BreakpointList,
+        Message::SetUsage,
+        Message::UnknownDebuggerCommand,
+        Message::StoppedByDebugger,
+        Message::ExpectLineNumber,
+        Message::BreakpointSet,
+        Message::BreakpointRemoved,
+        Message::NoBreakpoint,
+        Message::NoScriptLaunched,
+        Message::NeedsPausedScript,
+        Message::UnknownRequest,
+        Message::LaunchNeedsProgram,
+        Message::ProgramReadError,
+        Message::GlobalsScope,
+        Message::LocalsScope,
+        Message::NotAVariableName,
+        Message::DebuggerHelp,
+        Message::ErrorSeverity,
+        Message::WarningSeverity,
+        Message::AtEnd,
+        Message::AtToken,
+        Message::InScript,
+        Message::InFunction,
+    ];
+
+    /// Stable code of the diagnostic, or `None` for failures of the host
+    /// rather than the script.
+    pub fn code(self) -> Option<&'static str> {
+        let code = match self {
+            Message::UnterminatedString => "L0001",
+            Message::UnexpectedCharacter => "L0002",
+            Message::UnterminatedComment => "L0003",
+            Message::ExpectExpression => "L0101",
+            Message::ExpectRightParenAfterExpression => "L0102",
+            Message::ExpectColonAfterThenBranch => "L0103",
+            Message::ExpectRightParenAfterArguments => "L0104",
+            Message::TooManyArguments => "L0105",
+            Message::TooMuchNesting => "L0106",
+            Message::OutsideLoop => "L0107",
+            Message::TooManyParameters => "L0108",
+            Message::DuplicateParameter => "L0109",
+            Message::InvalidAssignmentTarget => "L0110",
+            Message::ExpectEndOfExpression => "L0111",
+            Message::ExpectLeftParenAfterFun => "L0112",
+            Message::ExpectParameterName => "L0113",
+            Message::ExpectRightParenAfterParameters => "L0114",
+            Message::ExpectLeftBraceBeforeBody => "L0115",
+            Message::ExpectRightBraceAfterBody => "L0116",
+            Message::OperandMustBeNumber => "L0301",
+            Message::OperandsMustBeNumbers => "L0302",
+            Message::OperandsMustBeNumbersOrStrings => "L0303",
+            Message::UndefinedVariable => "L0304",
+            Message::NotCallable => "L0305",
+            Message::WrongArity => "L0306",
+            Message::Interrupted => "L0307",
+            Message::EnvironmentNameMustBeString => "L0308",
+            Message::ExitCodeMustBeInteger => "L0309",
+            Message::ClockUnavailable => "L0310",
+            Message::StackOverflow => "L0311",
+            Message::StepLimitExceeded => "L0312",
+            Message::TimeLimitExceeded => "L0313",
+            Message::HeapLimitExceeded => "L0314",
+            Message::Sandboxed => "L0315",
+            Message::OperandMustBeInteger => "L0316",
+            Message::OperandsMustBeIntegers => "L0317",
+            Message::ShiftOutOfRange => "L0318",
+            Message::ArgumentIndexMustBeInteger => "L0319",
+            Message::SelfComparison => "L0401",
+            Message::UnknownLint => "L0402",
+            Message::UnusedParameter => "L0403",
+            Message::ShadowedParameter => "L0404",
+            Message::FileReadError
+            | Message::StdinReadError
+            | Message::LineReadError
+            | Message::UnknownErrorCode
+            | Message::NotFormatted
+            | Message::FileWriteError
+            | Message::WrongExample
+            | Message::RightExample
+            | Message::BreakpointList
+            | Message::SetUsage
+            | Message::UnknownDebuggerCommand
+            | Message::ExpectLineNumber
+            | Message::BreakpointSet
+            | Message::BreakpointRemoved
+            | Message::NoBreakpoint
+            | Message::NoScriptLaunched
+            | Message::NeedsPausedScript
+            | Message::UnknownRequest
+            | Message::LaunchNeedsProgram
+            | Message::ProgramReadError
+            | Message::GlobalsScope
+            | Message::LocalsScope
+            | Message::DebuggerHelp
+            | Message::NotAVariableName
+            | Message::ErrorSeverity
+            | Message::WarningSeverity
+            | Message::AtEnd
+            | Message::AtToken
+            | Message::InScript
+            | Message::InFunction
+            | Message::StoppedByDebugger => {
+                return None;
+            }
+        };
+
+        Some(code)
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Message::ALL
+            .into_iter()
+            .find(|message| message.code() == Some(code))
+    }
+}
+
+/// Templates use `{0}`, `{1}`, ... for their arguments.
+static CATALOG: &[(Message, Language, &str)] = &[
+    (
+        Message::UnexpectedCharacter,
+        Language::En,
+        "Unexpected character. {0}",
+    ),
+    (
+        Message::UnexpectedCharacter,
+        Language::PtBr,
+        "Caractere inesperado. {0}",
+    ),
+    (
+        Message::UnterminatedString,
+        Language::En,
+        "Unterminated string",
+    ),
+    (
+        Message::UnterminatedString,
+        Language::PtBr,
+        "String não terminada",
+    ),
+    (
+        Message::UnterminatedComment,
+        Language::En,
+        "Unterminated comment block",
+    ),
+    (
+        Message::UnterminatedComment,
+        Language::PtBr,
+        "Bloco de comentário não terminado",
+    ),
+    (
+        Message::ExpectColonAfterThenBranch,
+        Language::En,
+        "Expect ':' after then branch.",
+    ),
+    (
+        Message::ExpectColonAfterThenBranch,
+        Language::PtBr,
+        "Esperado ':' após o ramo then.",
+    ),
+    (
+        Message::ExpectRightParenAfterExpression,
+        Language::En,
+        "Expect ')' after expression.",
+    ),
+    (
+        Message::ExpectRightParenAfterExpression,
+        Language::PtBr,
+        "Esperado ')' após a expressão.",
+    ),
+    (
+        Message::ExpectExpression,
+        Language::En,
+        "Expect expression.",
+    ),
+    (
+        Message::ExpectExpression,
+        Language::PtBr,
+        "Esperada uma expressão.",
+    ),
+    (
+        Message::TooManyArguments,
+        Language::En,
+        "Can't have more than 255 arguments.",
+    ),
+    (
+        Message::TooManyArguments,
+        Language::PtBr,
+        "Não é possível ter mais de 255 argumentos.",
+    ),
+    (
+        Message::ExpectRightParenAfterArguments,
+        Language::En,
+        "Expect ')' after arguments.",
+    ),
+    (
+        Message::ExpectRightParenAfterArguments,
+        Language::PtBr,
+        "Esperado ')' após os argumentos.",
+    ),
+    (
+        Message::OperandMustBeNumber,
+        Language::En,
+        "Operand must be a number",
+    ),
+    (
+        Message::OperandMustBeNumber,
+        Language::PtBr,
+        "O operando deve ser um número",
+    ),
+    (
+        Message::OperandsMustBeNumbers,
+        Language::En,
+        "Operands must be numbers.",
+    ),
+    (
+        Message::OperandsMustBeNumbers,
+        Language::PtBr,
+        "Os operandos devem ser números.",
+    ),
+    (
+        Message::OperandsMustBeNumbersOrStrings,
+        Language::En,
+        "Operands must be two numbers or two strings.",
+    ),
+    (
+        Message::OperandsMustBeNumbersOrStrings,
+        Language::PtBr,
+        "Os operandos devem ser dois números ou duas strings.",
+    ),
+    (
+        Message::UndefinedVariable,
+        Language::En,
+        "Undefined variable '{0}'.",
+    ),
+    (
+        Message::UndefinedVariable,
+        Language::PtBr,
+        "Variável '{0}' não definida.",
+    ),
+    (
+        Message::NotCallable,
+        Language::En,
+        "Can only call functions and classes.",
+    ),
+    (
+        Message::NotCallable,
+        Language::PtBr,
+        "Só é possível chamar funções e classes.",
+    ),
+    (
+        Message::WrongArity,
+        Language::En,
+        "Expected {0} arguments but got {1}.",
+    ),
+    (
+        Message::WrongArity,
+        Language::PtBr,
+        "Esperados {0} argumentos, mas foram recebidos {1}.",
+    ),
+    (Message::Interrupted, Language::En, "Interrupted"),
+    (Message::Interrupted, Language::PtBr, "Interrompido"),
+    (
+        Message::EnvironmentNameMustBeString,
+        Language::En,
+        "Environment variable name must be a string.",
+    ),
+    (
+        Message::EnvironmentNameMustBeString,
+        Language::PtBr,
+        "O nome da variável de ambiente deve ser uma string.",
+    ),
+    (
+        Message::ExitCodeMustBeInteger,
+        Language::En,
+        "Exit code must be an integer between 0 and 255.",
+    ),
+    (
+        Message::ExitCodeMustBeInteger,
+        Language::PtBr,
+        "O código de saída deve ser um inteiro entre 0 e 255.",
+    ),
+    (
+        Message::ClockUnavailable,
+        Language::En,
+        "System clock is set before the Unix epoch.",
+    ),
+    (
+        Message::ClockUnavailable,
+        Language::PtBr,
+        "O relógio do sistema está antes da época Unix.",
+    ),
+    (
+        Message::FileReadError,
+        Language::En,
+        "Error reading the file! Provide a valid path.",
+    ),
+    (
+        Message::FileReadError,
+        Language::PtBr,
+        "Erro ao ler o arquivo! Informe um caminho válido.",
+    ),
+    (
+        Message::StdinReadError,
+        Language::En,
+        "Error reading the program from stdin",
+    ),
+    (
+        Message::StdinReadError,
+        Language::PtBr,
+        "Erro ao ler o programa da entrada padrão",
+    ),
+    (
+        Message::LineReadError,
+        Language::En,
+        "Error reading line: {0}",
+    ),
+    (
+        Message::LineReadError,
+        Language::PtBr,
+        "Erro ao ler a linha: {0}",
+    ),
+    (
+        Message::UnknownErrorCode,
+        Language::En,
+        "No explanation for error code '{0}'.",
+    ),
+    (
+        Message::UnknownErrorCode,
+        Language::PtBr,
+        "Nenhuma explicação para o código de erro '{0}'.",
+    ),
+    (
+        Message::SelfComparison,
+        Language::En,
+        "Comparing '{0}' with itself.",
+    ),
+    (
+        Message::SelfComparison,
+        Language::PtBr,
+        "Comparando '{0}' com ele mesmo.",
+    ),
+    (Message::UnknownLint, Language::En, "Unknown lint '{0}'."),
+    (
+        Message::UnknownLint,
+        Language::PtBr,
+        "Lint desconhecido '{0}'.",
+    ),
+    (
+        Message::UnusedParameter,
+        Language::En,
+        "Parameter '{0}' is never used.",
+    ),
+    (
+        Message::UnusedParameter,
+        Language::PtBr,
+        "O parâmetro '{0}' nunca é usado.",
+    ),
+    (
+        Message::ShadowedParameter,
+        Language::En,
+        "Parameter '{0}' shadows a parameter of an enclosing function.",
+    ),
+    (
+        Message::ShadowedParameter,
+        Language::PtBr,
+        "O parâmetro '{0}' esconde um parâmetro de uma função externa.",
+    ),
+    (Message::NotFormatted, Language::En, "{0} is not formatted."),
+    (
+        Message::NotFormatted,
+        Language::PtBr,
+        "{0} não está formatado.",
+    ),
+    (
+        Message::FileWriteError,
+        Language::En,
+        "Error writing {0}: {1}",
+    ),
+    (
+        Message::FileWriteError,
+        Language::PtBr,
+        "Erro ao escrever {0}: {1}",
+    ),
+    (
+        Message::TooMuchNesting,
+        Language::En,
+        "Expression is nested too deeply.",
+    ),
+    (
+        Message::TooMuchNesting,
+        Language::PtBr,
+        "Expressão aninhada profundamente demais.",
+    ),
+    (Message::StackOverflow, Language::En, "Stack overflow."),
+    (Message::StackOverflow, Language::PtBr, "Estouro de pilha."),
+    (
+        Message::StepLimitExceeded,
+        Language::En,
+        "Step limit exceeded.",
+    ),
+    (
+        Message::StepLimitExceeded,
+        Language::PtBr,
+        "Limite de passos excedido.",
+    ),
+    (
+        Message::TimeLimitExceeded,
+        Language::En,
+        "Time limit exceeded.",
+    ),
+    (
+        Message::TimeLimitExceeded,
+        Language::PtBr,
+        "Limite de tempo excedido.",
+    ),
+    (
+        Message::HeapLimitExceeded,
+        Language::En,
+        "Memory limit exceeded.",
+    ),
+    (
+        Message::HeapLimitExceeded,
+        Language::PtBr,
+        "Limite de memória excedido.",
+    ),
+    (
+        Message::Sandboxed,
+        Language::En,
+        "Not available in the sandbox.",
+    ),
+    (
+        Message::Sandboxed,
+        Language::PtBr,
+        "Indisponível no sandbox.",
+    ),
+    (
+        Message::OutsideLoop,
+        Language::En,
+        "Can't use '{0}' outside of a loop.",
+    ),
+    (
+        Message::OutsideLoop,
+        Language::PtBr,
+        "Não é possível usar '{0}' fora de um laço.",
+    ),
+    (
+        Message::TooManyParameters,
+        Language::En,
+        "Can't have more than 255 parameters.",
+    ),
+    (
+        Message::TooManyParameters,
+        Language::PtBr,
+        "Não é possível ter mais de 255 parâmetros.",
+    ),
+    (
+        Message::DuplicateParameter,
+        Language::En,
+        "Duplicate parameter '{0}'.",
+    ),
+    (
+        Message::DuplicateParameter,
+        Language::PtBr,
+        "Parâmetro '{0}' duplicado.",
+    ),
+    (
+        Message::OperandMustBeInteger,
+        Language::En,
+        "Operand must be an integer between -2^53 and 2^53 - 1.",
+    ),
+    (
+        Message::OperandMustBeInteger,
+        Language::PtBr,
+        "O operando deve ser um inteiro entre -2^53 e 2^53 - 1.",
+    ),
+    (
+        Message::OperandsMustBeIntegers,
+        Language::En,
+        "Operands must be integers between -2^53 and 2^53 - 1.",
+    ),
+    (
+        Message::OperandsMustBeIntegers,
+        Language::PtBr,
+        "Os operandos devem ser inteiros entre -2^53 e 2^53 - 1.",
+    ),
+    (
+        Message::ShiftOutOfRange,
+        Language::En,
+        "Shift out of range.",
+    ),
+    (
+        Message::ShiftOutOfRange,
+        Language::PtBr,
+        "Deslocamento fora do intervalo.",
+    ),
+    (
+        Message::InvalidAssignmentTarget,
+        Language::En,
+        "Invalid assignment target.",
+    ),
+    (
+        Message::InvalidAssignmentTarget,
+        Language::PtBr,
+        "Alvo de atribuição inválido.",
+    ),
+    (
+        Message::ArgumentIndexMustBeInteger,
+        Language::En,
+        "Argument index must be an integer.",
+    ),
+    (
+        Message::ArgumentIndexMustBeInteger,
+        Language::PtBr,
+        "O índice do argumento deve ser um inteiro.",
+    ),
+    (Message::WrongExample, Language::En, "Wrong:"),
+    (Message::WrongExample, Language::PtBr, "Errado:"),
+    (Message::RightExample, Language::En, "Right:"),
+    (Message::RightExample, Language::PtBr, "Certo:"),
+    (
+        Message::ExpectEndOfExpression,
+        Language::En,
+        "Expect end of expression.",
+    ),
+    (
+        Message::ExpectEndOfExpression,
+        Language::PtBr,
+        "Esperado o fim da expressão.",
+    ),
+    (
+        Message::ExpectLeftParenAfterFun,
+        Language::En,
+        "Expect '(' after 'fun'.",
+    ),
+    (
+        Message::ExpectLeftParenAfterFun,
+        Language::PtBr,
+        "Esperado '(' depois de 'fun'.",
+    ),
+    (
+        Message::ExpectParameterName,
+        Language::En,
+        "Expect parameter name.",
+    ),
+    (
+        Message::ExpectParameterName,
+        Language::PtBr,
+        "Esperado o nome de um parâmetro.",
+    ),
+    (
+        Message::ExpectRightParenAfterParameters,
+        Language::En,
+        "Expect ')' after parameters.",
+    ),
+    (
+        Message::ExpectRightParenAfterParameters,
+        Language::PtBr,
+        "Esperado ')' depois dos parâmetros.",
+    ),
+    (
+        Message::ExpectLeftBraceBeforeBody,
+        Language::En,
+        "Expect '{' before function body.",
+    ),
+    (
+        Message::ExpectLeftBraceBeforeBody,
+        Language::PtBr,
+        "Esperado '{' antes do corpo da função.",
+    ),
+    (
+        Message::ExpectRightBraceAfterBody,
+        Language::En,
+        "Expect '}' after function body.",
+    ),
+    (
+        Message::ExpectRightBraceAfterBody,
+        Language::PtBr,
+        "Esperado '}' depois do corpo da função.",
+    ),
+    (Message::BreakpointList, Language::En, "Breakpoints: {0}"),
+    (
+        Message::BreakpointList,
+        Language::PtBr,
+        "Pontos de parada: {0}",
+    ),
+    (Message::SetUsage, Language::En, "Usage: set NAME = EXPR"),
+    (Message::SetUsage, Language::PtBr, "Uso: set NOME = EXPR"),
+    (
+        Message::UnknownDebuggerCommand,
+        Language::En,
+        "Unknown command '{0}'. Try 'help'.",
+    ),
+    (
+        Message::UnknownDebuggerCommand,
+        Language::PtBr,
+        "Comando desconhecido '{0}'. Tente 'help'.",
+    ),
+    (
+        Message::ExpectLineNumber,
+        Language::En,
+        "Expected a line number.",
+    ),
+    (
+        Message::ExpectLineNumber,
+        Language::PtBr,
+        "Esperado um número de linha.",
+    ),
+    (
+        Message::BreakpointSet,
+        Language::En,
+        "Breakpoint on line {0}.",
+    ),
+    (
+        Message::BreakpointSet,
+        Language::PtBr,
+        "Ponto de parada na linha {0}.",
+    ),
+    (
+        Message::BreakpointRemoved,
+        Language::En,
+        "Removed the breakpoint on line {0}.",
+    ),
+    (
+        Message::BreakpointRemoved,
+        Language::PtBr,
+        "Removido o ponto de parada na linha {0}.",
+    ),
+    (
+        Message::NoBreakpoint,
+        Language::En,
+        "No breakpoint on line {0}.",
+    ),
+    (
+        Message::NoBreakpoint,
+        Language::PtBr,
+        "Nenhum ponto de parada na linha {0}.",
+    ),
+    (
+        Message::NoScriptLaunched,
+        Language::En,
+        "No script was launched.",
+    ),
+    (
+        Message::NoScriptLaunched,
+        Language::PtBr,
+        "Nenhum script foi iniciado.",
+    ),
+    (
+        Message::NeedsPausedScript,
+        Language::En,
+        "'{0}' needs a paused script.",
+    ),
+    (
+        Message::NeedsPausedScript,
+        Language::PtBr,
+        "'{0}' precisa de um script pausado.",
+    ),
+    (
+        Message::UnknownRequest,
+        Language::En,
+        "Unknown command '{0}'.",
+    ),
+    (
+        Message::UnknownRequest,
+        Language::PtBr,
+        "Comando desconhecido '{0}'.",
+    ),
+    (
+        Message::LaunchNeedsProgram,
+        Language::En,
+        "The launch request needs a 'program'.",
+    ),
+    (
+        Message::LaunchNeedsProgram,
+        Language::PtBr,
+        "A requisição launch precisa de um 'program'.",
+    ),
+    (
+        Message::ProgramReadError,
+        Language::En,
+        "Error reading {0}: {1}",
+    ),
+    (
+        Message::ProgramReadError,
+        Language::PtBr,
+        "Erro ao ler {0}: {1}",
+    ),
+    (Message::GlobalsScope, Language::En, "Globals"),
+    (Message::GlobalsScope, Language::PtBr, "Globais"),
+    (Message::LocalsScope, Language::En, "Locals"),
+    (Message::LocalsScope, Language::PtBr, "Locais"),
+    (
+        Message::DebuggerHelp,
+        Language::En,
+        "\
+break LINE     (b)   stop when execution reaches LINE
+delete LINE    (d)   remove the breakpoint on LINE
+breakpoints          list the breakpoints
+step           (s)   stop at the next expression
+next           (n)   stop at the next expression that isn't inside this one
+out            (o)   stop at the next expression outside the enclosing one
+continue       (c)   run until a breakpoint
+backtrace      (bt)  print the expressions being evaluated
+list           (l)   show the source around the current line
+print EXPR     (p)   evaluate EXPR and print its value
+set NAME = EXPR      assign EXPR to the variable NAME
+quit           (q)   stop the script
+",
+    ),
+    (
+        Message::DebuggerHelp,
+        Language::PtBr,
+        "\
+break LINHA    (b)   para quando a execução chegar à LINHA
+delete LINHA   (d)   remove o ponto de parada da LINHA
+breakpoints          lista os pontos de parada
+step           (s)   para na próxima expressão
+next           (n)   para na próxima expressão que não esteja dentro desta
+out            (o)   para na próxima expressão fora da que a contém
+continue       (c)   executa até um ponto de parada
+backtrace      (bt)  mostra as expressões sendo avaliadas
+list           (l)   mostra o código ao redor da linha atual
+print EXPR     (p)   avalia EXPR e mostra o seu valor
+set NOME = EXPR      atribui EXPR à variável NOME
+quit           (q)   encerra o script
+",
+    ),
+    (
+        Message::NotAVariableName,
+        Language::En,
+        "'{0}' is not a variable name.",
+    ),
+    (
+        Message::NotAVariableName,
+        Language::PtBr,
+        "'{0}' não é um nome de variável.",
+    ),
+    (Message::ErrorSeverity, Language::En, "Error"),
+    (Message::ErrorSeverity, Language::PtBr, "Erro"),
+    (Message::WarningSeverity, Language::En, "Warning"),
+    (Message::WarningSeverity, Language::PtBr, "Aviso"),
+    (Message::AtEnd, Language::En, "at end"),
+    (Message::AtEnd, Language::PtBr, "no fim"),
+    (Message::AtToken, Language::En, "at'{0}'"),
+    (Message::AtToken, Language::PtBr, "em'{0}'"),
+    (Message::InScript, Language::En, "in script"),
+    (Message::InScript, Language::PtBr, "no script"),
+    (Message::InFunction, Language::En, "in {0}()"),
+    (Message::InFunction, Language::PtBr, "em {0}()"),
+    (
+        Message::StoppedByDebugger,
+        Language::En,
+        "Stopped by the debugger.",
+    ),
+    (
+        Message::StoppedByDebugger,
+        Language::PtBr,
+        "Parado pelo depurador.",
+    ),
+];
+
+/// Longer descriptions of the coded messages, shown by `--explain`.
+static DESCRIPTIONS: &[(Message, Language, &str)] = &[
+    (
+        Message::UnexpectedCharacter,
+        Language::En,
+        "The scanner found a character that does not start any Lox token.",
+    ),
+    (
+        Message::UnexpectedCharacter,
+        Language::PtBr,
+        "O scanner encontrou um caractere que não inicia nenhum token de Lox.",
+    ),
+    (
+        Message::UnterminatedString,
+        Language::En,
+        "A string literal was opened with '\"' but the file ended before the closing quote.",
+    ),
+    (
+        Message::UnterminatedString,
+        Language::PtBr,
+        "Uma string foi aberta com '\"', mas o arquivo terminou antes das aspas de fechamento.",
+    ),
+    (
+        Message::UnterminatedComment,
+        Language::En,
+        "A block comment was opened with '/*' but never closed with '*/'. Block comments nest, so every '/*' needs its own '*/'.",
+    ),
+    (
+        Message::UnterminatedComment,
+        Language::PtBr,
+        "Um comentário de bloco foi aberto com '/*', mas nunca fechado com '*/'. Comentários de bloco se aninham, então cada '/*' precisa do seu próprio '*/'.",
+    ),
+    (
+        Message::ExpectColonAfterThenBranch,
+        Language::En,
+        "A conditional expression needs both branches: 'condition ? then : else'.",
+    ),
+    (
+        Message::ExpectColonAfterThenBranch,
+        Language::PtBr,
+        "Uma expressão condicional precisa dos dois ramos: 'condição ? então : senão'.",
+    ),
+    (
+        Message::ExpectRightParenAfterExpression,
+        Language::En,
+        "A parenthesized expression was opened with '(' but not closed.",
+    ),
+    (
+        Message::ExpectRightParenAfterExpression,
+        Language::PtBr,
+        "Uma expressão entre parênteses foi aberta com '(', mas não foi fechada.",
+    ),
+    (
+        Message::ExpectExpression,
+        Language::En,
+        "The parser expected a value, such as a literal, a variable, a call or a parenthesized expression, but found something else.",
+    ),
+    (
+        Message::ExpectExpression,
+        Language::PtBr,
+        "O parser esperava um valor, como um literal, uma variável, uma chamada ou uma expressão entre parênteses, mas encontrou outra coisa.",
+    ),
+    (
+        Message::TooManyArguments,
+        Language::En,
+        "A call can pass at most 255 arguments.",
+    ),
+    (
+        Message::TooManyArguments,
+        Language::PtBr,
+        "Uma chamada pode passar no máximo 255 argumentos.",
+    ),
+    (
+        Message::ExpectRightParenAfterArguments,
+        Language::En,
+        "The argument list of a call was opened with '(' but not closed.",
+    ),
+    (
+        Message::ExpectRightParenAfterArguments,
+        Language::PtBr,
+        "A lista de argumentos de uma chamada foi aberta com '(', mas não foi fechada.",
+    ),
+    (
+        Message::TooMuchNesting,
+        Language::En,
+        "Parentheses, unary operators, call arguments and conditionals can nest at most 64 deep, and chains of operators at most 256 deep, so that hostile input cannot overflow the stack.",
+    ),
+    (
+        Message::TooMuchNesting,
+        Language::PtBr,
+        "Parênteses, operadores unários, argumentos de chamadas e condicionais podem se aninhar no máximo 64 níveis, e cadeias de operadores no máximo 256, para que uma entrada hostil não estoure a pilha.",
+    ),
+    (
+        Message::OutsideLoop,
+        Language::En,
+        "'break' and 'continue' are reserved for loops, which the language doesn't have yet, so no expression can use them.",
+    ),
+    (
+        Message::OutsideLoop,
+        Language::PtBr,
+        "'break' e 'continue' são reservados para laços, que a linguagem ainda não tem, então nenhuma expressão pode usá-los.",
+    ),
+    (
+        Message::TooManyParameters,
+        Language::En,
+        "A lambda can declare at most 255 parameters.",
+    ),
+    (
+        Message::TooManyParameters,
+        Language::PtBr,
+        "Uma lambda pode declarar no máximo 255 parâmetros.",
+    ),
+    (
+        Message::DuplicateParameter,
+        Language::En,
+        "Each parameter of a lambda needs its own name, or the later one would hide the earlier.",
+    ),
+    (
+        Message::DuplicateParameter,
+        Language::PtBr,
+        "Cada parâmetro de uma lambda precisa de um nome próprio, senão o último esconderia o anterior.",
+    ),
+    (
+        Message::InvalidAssignmentTarget,
+        Language::En,
+        "'+=', '-=', '*=', '/=', '%=', '++' and '--' store their result, so they need a variable to store it in.",
+    ),
+    (
+        Message::InvalidAssignmentTarget,
+        Language::PtBr,
+        "'+=', '-=', '*=', '/=', '%=', '++' e '--' guardam o seu resultado, então precisam de uma variável onde guardá-lo.",
+    ),
+    (
+        Message::ExpectEndOfExpression,
+        Language::En,
+        "The parser read a whole expression, but more tokens follow it.",
+    ),
+    (
+        Message::ExpectEndOfExpression,
+        Language::PtBr,
+        "O parser leu uma expressão completa, mas há mais tokens depois dela.",
+    ),
+    (
+        Message::ExpectLeftParenAfterFun,
+        Language::En,
+        "An anonymous function starts with 'fun' and its parameter list in parentheses, even when it has no parameters.",
+    ),
+    (
+        Message::ExpectLeftParenAfterFun,
+        Language::PtBr,
+        "Uma função anônima começa com 'fun' e a sua lista de parâmetros entre parênteses, mesmo quando não tem parâmetros.",
+    ),
+    (
+        Message::ExpectParameterName,
+        Language::En,
+        "Parameters are names separated by commas, so the list can't hold other expressions or end with a comma.",
+    ),
+    (
+        Message::ExpectParameterName,
+        Language::PtBr,
+        "Parâmetros são nomes separados por vírgulas, então a lista não pode ter outras expressões nem terminar com vírgula.",
+    ),
+    (
+        Message::ExpectRightParenAfterParameters,
+        Language::En,
+        "Parameter names are separated by commas, and the list ends with ')'.",
+    ),
+    (
+        Message::ExpectRightParenAfterParameters,
+        Language::PtBr,
+        "Os nomes dos parâmetros são separados por vírgulas, e a lista termina com ')'.",
+    ),
+    (
+        Message::ExpectLeftBraceBeforeBody,
+        Language::En,
+        "The body of a 'fun' function goes between braces. The arrow form '(a) => a' needs no braces.",
+    ),
+    (
+        Message::ExpectLeftBraceBeforeBody,
+        Language::PtBr,
+        "O corpo de uma função 'fun' fica entre chaves. A forma com seta '(a) => a' não precisa de chaves.",
+    ),
+    (
+        Message::ExpectRightBraceAfterBody,
+        Language::En,
+        "The body of a 'fun' function is a single expression, optionally after 'return' and before ';', closed by '}'.",
+    ),
+    (
+        Message::ExpectRightBraceAfterBody,
+        Language::PtBr,
+        "O corpo de uma função 'fun' é uma única expressão, opcionalmente depois de 'return' e antes de ';', fechada por '}'.",
+    ),
+    (
+        Message::OperandMustBeNumber,
+        Language::En,
+        "Negation and arithmetic or comparison operators only work on numbers.",
+    ),
+    (
+        Message::OperandMustBeNumber,
+        Language::PtBr,
+        "A negação e os operadores aritméticos ou de comparação só funcionam com números.",
+    ),
+    (
+        Message::OperandsMustBeNumbers,
+        Language::En,
+        "Arithmetic and comparison operators, other than '+', only work on two numbers.",
+    ),
+    (
+        Message::OperandsMustBeNumbers,
+        Language::PtBr,
+        "Os operadores aritméticos e de comparação, exceto '+', só funcionam com dois números.",
+    ),
+    (
+        Message::OperandsMustBeNumbersOrStrings,
+        Language::En,
+        "'+' adds two numbers or concatenates two strings; it does not convert between them.",
+    ),
+    (
+        Message::OperandsMustBeNumbersOrStrings,
+        Language::PtBr,
+        "'+' soma dois números ou concatena duas strings; ele não converte um no outro.",
+    ),
+    (
+        Message::UndefinedVariable,
+        Language::En,
+        "The name is not defined as a global, so it has no value to read.",
+    ),
+    (
+        Message::UndefinedVariable,
+        Language::PtBr,
+        "O nome não está definido como global, então não há valor para ler.",
+    ),
+    (
+        Message::NotCallable,
+        Language::En,
+        "Only functions can be called; the callee evaluated to another kind of value.",
+    ),
+    (
+        Message::NotCallable,
+        Language::PtBr,
+        "Só funções podem ser chamadas; a expressão chamada resultou em outro tipo de valor.",
+    ),
+    (
+        Message::WrongArity,
+        Language::En,
+        "A function was called with a different number of arguments than it declares.",
+    ),
+    (
+        Message::WrongArity,
+        Language::PtBr,
+        "Uma função foi chamada com um número de argumentos diferente do que declara.",
+    ),
+    (
+        Message::Interrupted,
+        Language::En,
+        "The script was stopped with Ctrl-C while it was running.",
+    ),
+    (
+        Message::Interrupted,
+        Language::PtBr,
+        "O script foi interrompido com Ctrl-C enquanto executava.",
+    ),
+    (
+        Message::EnvironmentNameMustBeString,
+        Language::En,
+        "env() looks up an environment variable by name, which must be a string.",
+    ),
+    (
+        Message::EnvironmentNameMustBeString,
+        Language::PtBr,
+        "env() busca uma variável de ambiente pelo nome, que deve ser uma string.",
+    ),
+    (
+        Message::ExitCodeMustBeInteger,
+        Language::En,
+        "exit() takes the process exit status, which must be a whole number from 0 to 255.",
+    ),
+    (
+        Message::ExitCodeMustBeInteger,
+        Language::PtBr,
+        "exit() recebe o código de saída do processo, que deve ser um número inteiro de 0 a 255.",
+    ),
+    (
+        Message::ClockUnavailable,
+        Language::En,
+        "clock() counts seconds since the Unix epoch, and the system clock is set to an earlier date.",
+    ),
+    (
+        Message::ClockUnavailable,
+        Language::PtBr,
+        "clock() conta os segundos desde a época Unix, e o relógio do sistema está numa data anterior.",
+    ),
+    (
+        Message::StackOverflow,
+        Language::En,
+        "Evaluation went deeper than --max-depth allows. Raise the limit or simplify the expression.",
+    ),
+    (
+        Message::StackOverflow,
+        Language::PtBr,
+        "A avaliação foi mais fundo do que --max-depth permite. Aumente o limite ou simplifique a expressão.",
+    ),
+    (
+        Message::StepLimitExceeded,
+        Language::En,
+        "The script evaluated more expressions than --max-steps allows.",
+    ),
+    (
+        Message::StepLimitExceeded,
+        Language::PtBr,
+        "O script avaliou mais expressões do que --max-steps permite.",
+    ),
+    (
+        Message::TimeLimitExceeded,
+        Language::En,
+        "The script ran for longer than --timeout allows.",
+    ),
+    (
+        Message::TimeLimitExceeded,
+        Language::PtBr,
+        "O script executou por mais tempo do que --timeout permite.",
+    ),
+    (
+        Message::HeapLimitExceeded,
+        Language::En,
+        "The strings the script built add up to more bytes than --max-heap allows.",
+    ),
+    (
+        Message::HeapLimitExceeded,
+        Language::PtBr,
+        "As strings que o script construiu somam mais bytes do que --max-heap permite.",
+    ),
+    (
+        Message::Sandboxed,
+        Language::En,
+        "--sandbox disables the natives that reach outside the interpreter: args(), argc(), arg(), env() and exit().",
+    ),
+    (
+        Message::Sandboxed,
+        Language::PtBr,
+        "--sandbox desativa as funções nativas que alcançam fora do interpretador: args(), argc(), arg(), env() e exit().",
+    ),
+    (
+        Message::OperandMustBeInteger,
+        Language::En,
+        "'~' works on whole numbers that fit in 54-bit two's complement, the range a Lox number holds exactly.",
+    ),
+    (
+        Message::OperandMustBeInteger,
+        Language::PtBr,
+        "'~' funciona com números inteiros que cabem em complemento de dois de 54 bits, a faixa que um número de Lox guarda com exatidão.",
+    ),
+    (
+        Message::OperandsMustBeIntegers,
+        Language::En,
+        "'&', '|', '^', '<<' and '>>' work on whole numbers that fit in 54-bit two's complement, the range a Lox number holds exactly.",
+    ),
+    (
+        Message::OperandsMustBeIntegers,
+        Language::PtBr,
+        "'&', '|', '^', '<<' e '>>' funcionam com números inteiros que cabem em complemento de dois de 54 bits, a faixa que um número de Lox guarda com exatidão.",
+    ),
+    (
+        Message::ShiftOutOfRange,
+        Language::En,
+        "Shift counts can't be negative, and the result of '<<' must stay between -2^53 and 2^53 - 1.",
+    ),
+    (
+        Message::ShiftOutOfRange,
+        Language::PtBr,
+        "O deslocamento não pode ser negativo, e o resultado de '<<' deve ficar entre -2^53 e 2^53 - 1.",
+    ),
+    (
+        Message::ArgumentIndexMustBeInteger,
+        Language::En,
+        "arg() takes the position of a script argument, counting from 0. Positions past argc() give nil.",
+    ),
+    (
+        Message::ArgumentIndexMustBeInteger,
+        Language::PtBr,
+        "arg() recebe a posição de um argumento do script, contando a partir de 0. Posições além de argc() dão nil.",
+    ),
+    (
+        Message::SelfComparison,
+        Language::En,
+        "Comparing a variable with itself always gives the same answer, so it is usually a typo for another variable. Silence it with '// lox: allow(self_comparison)'.",
+    ),
+    (
+        Message::SelfComparison,
+        Language::PtBr,
+        "Comparar uma variável com ela mesma sempre dá a mesma resposta, então costuma ser um erro de digitação no lugar de outra variável. Silencie com '// lox: allow(self_comparison)'.",
+    ),
+    (
+        Message::UnknownLint,
+        Language::En,
+        "A '// lox:' pragma names a lint that does not exist, so it has no effect.",
+    ),
+    (
+        Message::UnknownLint,
+        Language::PtBr,
+        "Um pragma '// lox:' cita um lint que não existe, então não tem efeito.",
+    ),
+    (
+        Message::UnusedParameter,
+        Language::En,
+        "A function never reads this parameter, so the argument passed for it is thrown away. Start its name with '_' to keep it anyway, or silence it with '// lox: allow(unused_parameters)'.",
+    ),
+    (
+        Message::UnusedParameter,
+        Language::PtBr,
+        "Uma função nunca lê este parâmetro, então o argumento passado a ele é descartado. Comece o nome com '_' para mantê-lo mesmo assim, ou silencie com '// lox: allow(unused_parameters)'.",
+    ),
+    (
+        Message::ShadowedParameter,
+        Language::En,
+        "A nested function has a parameter with the same name as one of the function around it, so the outer one can't be reached inside it. Silence it with '// lox: allow(shadowed_parameters)'.",
+    ),
+    (
+        Message::ShadowedParameter,
+        Language::PtBr,
+        "Uma função aninhada tem um parâmetro com o mesmo nome de um da função ao redor, então o de fora não pode ser alcançado dentro dela. Silencie com '// lox: allow(shadowed_parameters)'.",
+    ),
+];
+
+/// Wording of the reference jlox where it differs from ours.
+static JLOX: &[(Message, &str)] = &[
+    (Message::UnexpectedCharacter, "Unexpected character."),
+    (Message::UnterminatedString, "Unterminated string."),
+    (Message::OperandMustBeNumber, "Operand must be a number."),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Locale {
+    pub language: Language,
+    pub compat: Compat,
+}
+
+impl Locale {
+    pub fn new(language: Language, compat: Compat) -> Self {
+        Self { language, compat }
+    }
+
+    pub fn text(&self, message: Message, args: &[&str]) -> String {
+        let template = match self.compat {
+            Compat::Rlox => template(message, self.language),
+            // jlox only speaks English, so compat mode ignores the language.
+            Compat::Jlox => JLOX
+                .iter()
+                .find(|(key, _)| *key == message)
+                .map(|(_, template)| *template)
+                .or_else(|| template(message, Language::En)),
+        };
+
+        args.iter().enumerate().fold(
+            template.unwrap_or_default().to_string(),
+            |text, (index, arg)| text.replace(&format!("{{{}}}", index), arg),
+        )
+    }
+
+    /// Describes a coded message at length, or returns "" for uncoded ones.
+    pub fn description(&self, message: Message) -> &'static str {
+        lookup(DESCRIPTIONS, message, self.language).unwrap_or_default()
+    }
+}
+
+fn template(message: Message, language: Language) -> Option<&'static str> {
+    lookup(CATALOG, message, language)
+}
+
+fn lookup(
+    table: &[(Message, Language, &'static str)],
+    message: Message,
+    language: Language,
+) -> Option<&'static str> {
+    table
+        .iter()
+        .find(|(key, lang, _)| *key == message && *lang == language)
+        .map(|(_, _, template)| *template)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_message_is_translated() {
+        for message in Message::ALL {
+            for language in Language::ALL {
+                let count = CATALOG
+                    .iter()
+                    .filter(|(key, lang, _)| *key == message && *lang == language)
+                    .count();
+
+                assert_eq!(
+                    count, 1,
+                    "{:?} has {} {:?} entries",
+                    message, count, language
+                );
+            }
+        }
+
+        assert_eq!(CATALOG.len(), Message::ALL.len() * Language::ALL.len());
+    }
+
+    #[test]
+    fn test_every_code_is_described() {
+        for message in Message::ALL {
+            for language in Language::ALL {
+                let count = DESCRIPTIONS
+                    .iter()
+                    .filter(|(key, lang, _)| *key == message && *lang == language)
+                    .count();
+
+                assert_eq!(
+                    count,
+                    usize::from(message.code().is_some()),
+                    "{:?} has {} {:?} descriptions",
+                    message,
+                    count,
+                    language
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_text() {
+        let en = Locale::new(Language::En, Compat::Rlox);
+        let pt_br = Locale::new(Language::PtBr, Compat::Rlox);
+        let jlox = Locale::new(Language::PtBr, Compat::Jlox);
+
+        assert_eq!(
+            en.text(Message::WrongArity, &["1", "2"]),
+            "Expected 1 arguments but got 2."
+        );
+        assert_eq!(
+            pt_br.text(Message::UndefinedVariable, &["x"]),
+            "Variável 'x' não definida."
+        );
+        assert_eq!(
+            jlox.text(Message::UnexpectedCharacter, &["@"]),
+            "Unexpected character."
+        );
+        assert_eq!(
+            jlox.text(Message::ExpectExpression, &[]),
+            "Expect expression."
+        );
+    }
+
+    #[test]
+    fn test_codes_are_unique() {
+        for message in Message::ALL {
+            if let Some(code) = message.code() {
+                assert_eq!(Message::from_code(code), Some(message));
+            }
+        }
+
+        assert_eq!(
+            Message::from_code("L0102"),
+            Some(Message::ExpectRightParenAfterExpression)
+        );
+        assert_eq!(Message::from_code("L9999"), None);
+    }
+
+    #[test]
+    fn test_from_locale() {
+        assert_eq!(Language::from_locale("pt_BR.UTF-8"), Language::PtBr);
+        assert_eq!(Language::from_locale("en_US.UTF-8"), Language::En);
+        assert_eq!(Language::from_locale("C"), Language::En);
+    }
+}
@@ -0,0 +1,156 @@
+use std::{
+    env,
+    fmt::{self, Debug, Display},
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{interpreter::Interpreter, messages::Message, token::LiteralType};
+
+/// Built-in natives report catalog messages so they follow the locale, while
+/// natives defined by embedders report their own text.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NativeError {
+    Custom(String),
+    Message(Message),
+    /// `exit()` was called with this status.
+    Exit(i32),
+}
+
+type NativeFn = dyn Fn(&[LiteralType]) -> Result<LiteralType, NativeError>;
+
+#[derive(Clone)]
+pub struct NativeFunction {
+    pub name: String,
+    pub arity: usize,
+    function: Rc<NativeFn>,
+}
+
+impl NativeFunction {
+    pub fn new<F>(name: &str, arity: usize, function: F) -> Self
+    where
+        F: Fn(&[LiteralType]) -> Result<LiteralType, String> + 'static,
+    {
+        Self::builtin(name, arity, move |arguments| {
+            function(arguments).map_err(NativeError::Custom)
+        })
+    }
+
+    fn builtin<F>(name: &str, arity: usize, function: F) -> Self
+    where
+        F: Fn(&[LiteralType]) -> Result<LiteralType, NativeError> + 'static,
+    {
+        Self {
+            name: name.to_string(),
+            arity,
+            function: Rc::new(function),
+        }
+    }
+
+    pub(crate) fn call(&self, arguments: &[LiteralType]) -> Result<LiteralType, NativeError> {
+        (self.function)(arguments)
+    }
+}
+
+impl PartialEq for NativeFunction {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.function, &other.function)
+    }
+}
+
+impl Display for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<native fn>")
+    }
+}
+
+impl Debug for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NativeFunction")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .finish()
+    }
+}
+
+/// The natives rlox adds to jlox's `clock()`.
+pub const EXTENSIONS: [&str; 5] = ["args", "argc", "arg", "env", "exit"];
+
+pub fn define_globals(interpreter: &mut Interpreter, script_args: Vec<String>) {
+    define_builtin(interpreter, "clock", 0, |_| {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| NativeError::Message(Message::ClockUnavailable))?;
+
+        Ok(LiteralType::Number(now.as_secs_f64()))
+    });
+
+    let script_args = Rc::new(script_args);
+
+    let args = Rc::clone(&script_args);
+    define_builtin(interpreter, "args", 0, move |_| {
+        Ok(LiteralType::String(args.join(" ")))
+    });
+
+    let args = Rc::clone(&script_args);
+    define_builtin(interpreter, "argc", 0, move |_| {
+        Ok(LiteralType::Number(args.len() as f64))
+    });
+
+    // args() joins the arguments with spaces, so a script that needs to
+    // tell "a b" from "a" "b" reads them one at a time.
+    define_builtin(interpreter, "arg", 1, move |arguments| match arguments[0] {
+        LiteralType::Number(index) if index.fract() == 0.0 => {
+            let arg = (index >= 0.0)
+                .then(|| script_args.get(index as usize))
+                .flatten();
+
+            Ok(arg.cloned().map_or(LiteralType::Nil, LiteralType::String))
+        }
+        _ => Err(NativeError::Message(Message::ArgumentIndexMustBeInteger)),
+    });
+}
+
+/// Natives that read the host's environment and end the script, which an
+/// embedder may not want a script to reach.
+pub fn define_process_natives(interpreter: &mut Interpreter) {
+    define_builtin(interpreter, "env", 1, |arguments| match &arguments[0] {
+        LiteralType::String(name) => Ok(env::var(name)
+            .map(LiteralType::String)
+            .unwrap_or(LiteralType::Nil)),
+        _ => Err(NativeError::Message(Message::EnvironmentNameMustBeString)),
+    });
+
+    define_builtin(interpreter, "exit", 1, |arguments| match arguments[0] {
+        LiteralType::Number(code) if code.fract() == 0.0 && (0.0..=255.0).contains(&code) => {
+            Err(NativeError::Exit(code as i32))
+        }
+        _ => Err(NativeError::Message(Message::ExitCodeMustBeInteger)),
+    });
+}
+
+/// Replaces the natives that reach outside the interpreter, into the process
+/// and its environment, with ones that fail.
+pub fn sandbox(interpreter: &mut Interpreter) {
+    for (name, arity) in [
+        ("args", 0),
+        ("argc", 0),
+        ("arg", 1),
+        ("env", 1),
+        ("exit", 1),
+    ] {
+        define_builtin(interpreter, name, arity, |_| {
+            Err(NativeError::Message(Message::Sandboxed))
+        });
+    }
+}
+
+fn define_builtin<F>(interpreter: &mut Interpreter, name: &str, arity: usize, function: F)
+where
+    F: Fn(&[LiteralType]) -> Result<LiteralType, NativeError> + 'static,
+{
+    interpreter.define_global(
+        name,
+        LiteralType::NativeFunction(NativeFunction::builtin(name, arity, function)),
+    );
+}
@@ -1,13 +1,61 @@
 use crate::{
+    compat::Compat,
+    cst::{self, Event, SyntaxKind, SyntaxNode},
     error_handler::ErrorHandler,
-    expr::{Binary, Comma, Expr, Grouping, Literal, Ternary, Unary},
+    expr::{
+        Assign, Binary, Call, Comma, Expr, Grouping, Increment, Lambda, Literal, Ternary, Unary,
+        Variable,
+    },
+    messages::Message,
     token::{LiteralType, Token},
     token_type::TokenType,
 };
 
+/// How deeply parentheses, unary operators, call arguments and conditional
+/// branches can nest, which is as deep as the parser recurses. C compilers
+/// must accept at least 63 levels of parentheses, and deeper input is more
+/// likely hostile than handwritten.
+pub const MAX_NESTING: usize = 64;
+
+/// How deep the syntax tree can get, which bounds the recursion of every
+/// pass over it. Chains of binary operators don't recurse in the parser but
+/// add a level per operator.
+pub const MAX_DEPTH: usize = 256;
+
+/// The binary operators, from the loosest level to the tightest. The
+/// bitwise operators sit between equality and comparison, in C's order, but
+/// bind tighter than `==` so `flags & 4 == 4` means `(flags & 4) == 4`.
+/// Shifts bind between comparison and term, as in C.
+const BINARY_LEVELS: [&[TokenType]; 8] = [
+    &[TokenType::BangEqual, TokenType::EqualEqual],
+    &[TokenType::Pipe],
+    &[TokenType::Caret],
+    &[TokenType::Ampersand],
+    &[
+        TokenType::Greater,
+        TokenType::GreaterEqual,
+        TokenType::Less,
+        TokenType::LessEqual,
+    ],
+    &[TokenType::LessLess, TokenType::GreaterGreater],
+    &[TokenType::Minus, TokenType::Plus],
+    &[
+        TokenType::Slash,
+        TokenType::Star,
+        TokenType::Percent,
+        TokenType::Div,
+    ],
+];
+
 pub struct Parser<'a> {
     tokens: Vec<Token>,
     current: usize,
+    /// Recursion of the parser, bounded by `MAX_NESTING`.
+    nesting: usize,
+    /// Depth of the tree being built, bounded by `MAX_DEPTH`.
+    depth: usize,
+    /// Recorded only by a lossless parse, to build the concrete syntax tree.
+    events: Option<Vec<Event>>,
     error_handler: &'a mut ErrorHandler,
 }
 
@@ -15,174 +63,490 @@ impl<'a> Parser<'a> {
     pub fn new(tokens: Vec<Token>, error_handler: &'a mut ErrorHandler) -> Self {
         Self {
             current: 0,
+            nesting: 0,
+            depth: 0,
             tokens,
+            events: None,
             error_handler,
         }
     }
 
+    pub fn lossless(tokens: Vec<Token>, error_handler: &'a mut ErrorHandler) -> Self {
+        Self {
+            events: Some(Vec::new()),
+            ..Self::new(tokens, error_handler)
+        }
+    }
+
     pub fn parse(&mut self) -> Option<Expr> {
-        let result = self.expression();
+        let expr = self.expression().ok()?;
+        self.end().ok()?;
 
-        match result {
-            Ok(result) => Some(result),
-            Err(_) => None,
-        }
+        Some(expr)
+    }
+
+    /// Parses into a concrete syntax tree, which needs a lossless parser.
+    pub fn parse_cst(&mut self) -> Option<SyntaxNode> {
+        self.expression().ok()?;
+        self.end().ok()?;
+        let events = self.events.take()?;
+
+        Some(cst::build(events, self.tokens.clone()))
     }
 
     fn comma(&mut self) -> Result<Expr, String> {
+        let checkpoint = self.checkpoint();
+        let depth = self.depth;
         let mut expr = self.expression()?;
 
-        while self.match_token(vec![TokenType::Comma]) {
+        // jlox has commas only between arguments.
+        while !self.is_jlox() && self.match_token(vec![TokenType::Comma]) {
+            self.deepen()?;
             let right = self.expression()?;
-            expr = Expr::Comma(Comma {
+            self.node_at(checkpoint, SyntaxKind::Comma);
+            expr = Expr::Comma(Box::new(Comma {
                 left: Box::new(expr),
                 right: Box::new(right),
-            })
+            }))
         }
 
+        self.depth = depth;
         Ok(expr)
     }
 
     fn expression(&mut self) -> Result<Expr, String> {
+        let expr = self.assignment()?;
+
+        Ok(expr)
+    }
+
+    /// Compound assignment groups to the right, so `a += b += 1` adds to `b`
+    /// first.
+    fn assignment(&mut self) -> Result<Expr, String> {
+        let checkpoint = self.checkpoint();
         let expr = self.ternary()?;
 
-        return Ok(expr);
+        if self.match_token(vec![
+            TokenType::PlusEqual,
+            TokenType::MinusEqual,
+            TokenType::StarEqual,
+            TokenType::SlashEqual,
+            TokenType::PercentEqual,
+        ]) {
+            let operator = self.previous();
+            self.nest()?;
+            let value = self.assignment()?;
+            self.unnest();
+
+            let Expr::Variable(variable) = expr else {
+                self.error(&operator, Message::InvalidAssignmentTarget);
+                return Ok(expr);
+            };
+
+            self.node_at(checkpoint, SyntaxKind::Assign);
+            return Ok(Expr::Assign(Box::new(Assign {
+                name: variable.name,
+                operator,
+                value: Box::new(value),
+            })));
+        }
+
+        Ok(expr)
     }
 
     fn ternary(&mut self) -> Result<Expr, String> {
-        let mut expr = self.equality()?;
+        let checkpoint = self.checkpoint();
+        let mut expr = self.binary(0)?;
 
         if self.match_token(vec![TokenType::Question]) {
+            self.nest()?;
             let condition = expr;
-            let then_branch = self.equality()?;
-            self.consume(&TokenType::Colon, "Expect ':' after then branch.")?;
+            let then_branch = self.binary(0)?;
+            self.consume(&TokenType::Colon, Message::ExpectColonAfterThenBranch)?;
             let else_branch = self.ternary()?;
-            expr = Expr::Ternary(Ternary {
+            self.node_at(checkpoint, SyntaxKind::Ternary);
+            expr = Expr::Ternary(Box::new(Ternary {
                 condition: Box::new(condition),
                 then_branch: Box::new(then_branch),
                 else_branch: Box::new(else_branch),
-            })
+            }));
+            self.unnest();
         };
 
         Ok(expr)
     }
 
-    fn equality(&mut self) -> Result<Expr, String> {
-        let mut expr = self.comparison()?;
+    /// Parses the binary operators that bind at `level` or tighter. Each
+    /// operand is a single call, whatever its level, which keeps the
+    /// recursion through parentheses shallow.
+    fn binary(&mut self, level: usize) -> Result<Expr, String> {
+        let checkpoint = self.checkpoint();
+        let depth = self.depth;
+        let mut expr = self.unary()?;
 
-        while self.match_token(vec![TokenType::BangEqual, TokenType::EqualEqual]) {
-            let operator = self.previous();
-            let right = self.comparison()?;
-            expr = Expr::Binary(Binary {
+        while let Some(operator_level) = self.binary_level(level) {
+            let operator = self.advance();
+            self.deepen()?;
+            let right = self.binary(operator_level + 1)?;
+            self.node_at(checkpoint, SyntaxKind::Binary);
+            expr = Expr::Binary(Box::new(Binary {
                 left: Box::new(expr),
                 operator,
                 right: Box::new(right),
-            })
+            }))
         }
 
+        self.depth = depth;
         Ok(expr)
     }
 
-    fn comparison(&mut self) -> Result<Expr, String> {
-        let mut expr = self.term()?;
+    /// The level of the binary operator ahead, if it binds at `level` or
+    /// tighter.
+    fn binary_level(&self, level: usize) -> Option<usize> {
+        let token_type = &self.tokens[self.current].token_type;
 
-        while self.match_token(vec![
-            TokenType::Greater,
-            TokenType::GreaterEqual,
-            TokenType::Less,
-            TokenType::LessEqual,
-        ]) {
+        (level..BINARY_LEVELS.len()).find(|&level| BINARY_LEVELS[level].contains(token_type))
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        let checkpoint = self.checkpoint();
+
+        if self.match_token(vec![TokenType::Bang, TokenType::Minus, TokenType::Tilde]) {
             let operator = self.previous();
-            let right = self.term()?;
-            expr = Expr::Binary(Binary {
-                left: Box::new(expr),
+            self.nest()?;
+            let right = self.unary()?;
+            self.unnest();
+            self.node_at(checkpoint, SyntaxKind::Unary);
+            return Ok(Expr::Unary(Box::new(Unary {
                 operator,
-                right: Box::from(right),
-            });
+                right: Box::new(right),
+            })));
         }
 
-        Ok(expr)
+        if self.match_token(vec![TokenType::PlusPlus, TokenType::MinusMinus]) {
+            let operator = self.previous();
+            self.nest()?;
+            let target = self.unary()?;
+            self.unnest();
+            return Ok(self.increment(checkpoint, operator, target, true));
+        }
+
+        self.power()
     }
 
-    fn term(&mut self) -> Result<Expr, String> {
-        let mut expr = self.factor()?;
+    /// Exponentiation binds tighter than the unary operators on its left and
+    /// looser than those on its right, so `-2 ** 2` is `-(2 ** 2)` and
+    /// `2 ** -1` is `2 ** (-1)`. It groups to the right: `2 ** 3 ** 2` is
+    /// `2 ** (3 ** 2)`.
+    fn power(&mut self) -> Result<Expr, String> {
+        let checkpoint = self.checkpoint();
+        let expr = self.postfix()?;
 
-        while self.match_token(vec![TokenType::Minus, TokenType::Plus]) {
+        if self.match_token(vec![TokenType::StarStar]) {
             let operator = self.previous();
-            let right = self.factor()?;
-            expr = Expr::Binary(Binary {
+            self.nest()?;
+            let right = self.unary()?;
+            self.unnest();
+            self.node_at(checkpoint, SyntaxKind::Binary);
+            return Ok(Expr::Binary(Box::new(Binary {
                 left: Box::new(expr),
                 operator,
                 right: Box::new(right),
-            })
+            })));
         }
 
         Ok(expr)
     }
 
-    fn factor(&mut self) -> Result<Expr, String> {
-        let mut expr = self.unary()?;
+    fn postfix(&mut self) -> Result<Expr, String> {
+        let checkpoint = self.checkpoint();
+        let expr = self.call()?;
 
-        while self.match_token(vec![TokenType::Slash, TokenType::Star]) {
+        if self.match_token(vec![TokenType::PlusPlus, TokenType::MinusMinus]) {
             let operator = self.previous();
-            let right = self.unary()?;
-            expr = Expr::Binary(Binary {
-                left: Box::new(expr),
-                operator,
-                right: Box::new(right),
-            })
+            return Ok(self.increment(checkpoint, operator, expr, false));
         }
 
         Ok(expr)
     }
 
-    fn unary(&mut self) -> Result<Expr, String> {
-        if self.match_token(vec![TokenType::Bang, TokenType::Minus]) {
-            let operator = self.previous();
-            let right = self.unary()?;
-            return Ok(Expr::Unary(Unary {
-                operator,
-                right: Box::new(right),
-            }));
+    /// Reports a target that isn't a variable, then carries on with it.
+    fn increment(
+        &mut self,
+        checkpoint: usize,
+        operator: Token,
+        target: Expr,
+        prefix: bool,
+    ) -> Expr {
+        let Expr::Variable(variable) = target else {
+            self.error(&operator, Message::InvalidAssignmentTarget);
+            return target;
+        };
+
+        self.node_at(checkpoint, SyntaxKind::Increment);
+        Expr::Increment(Box::new(Increment {
+            name: variable.name,
+            operator,
+            prefix,
+        }))
+    }
+
+    fn call(&mut self) -> Result<Expr, String> {
+        let checkpoint = self.checkpoint();
+        let depth = self.depth;
+        let mut expr = self.primary()?;
+
+        while self.match_token(vec![TokenType::LeftParen]) {
+            self.deepen()?;
+            expr = self.finish_call(expr)?;
+            self.node_at(checkpoint, SyntaxKind::Call);
         }
 
-        self.primary()
+        self.depth = depth;
+        Ok(expr)
+    }
+
+    fn finish_call(&mut self, callee: Expr) -> Result<Expr, String> {
+        let mut arguments = Vec::new();
+
+        if !self.check(&TokenType::RightParen) {
+            self.nest()?;
+
+            loop {
+                if arguments.len() >= 255 {
+                    self.error(&self.peek(), Message::TooManyArguments);
+                }
+
+                arguments.push(self.expression()?);
+
+                if !self.match_token(vec![TokenType::Comma]) {
+                    break;
+                }
+            }
+
+            self.unnest();
+        }
+
+        let paren = self.consume(
+            &TokenType::RightParen,
+            Message::ExpectRightParenAfterArguments,
+        )?;
+
+        Ok(Expr::Call(Box::new(Call {
+            callee: Box::new(callee),
+            paren,
+            arguments,
+        })))
     }
 
     fn primary(&mut self) -> Result<Expr, String> {
+        let checkpoint = self.checkpoint();
+
         if self.match_token(vec![TokenType::False]) {
-            return Ok(Expr::Literal(Literal {
+            self.node_at(checkpoint, SyntaxKind::Literal);
+            return Ok(Expr::Literal(Box::new(Literal {
                 value: LiteralType::Bool(false),
-            }));
+            })));
         };
 
         if self.match_token(vec![TokenType::True]) {
-            return Ok(Expr::Literal(Literal {
+            self.node_at(checkpoint, SyntaxKind::Literal);
+            return Ok(Expr::Literal(Box::new(Literal {
                 value: LiteralType::Bool(true),
-            }));
+            })));
         }
 
         if self.match_token(vec![TokenType::Nil]) {
-            return Ok(Expr::Literal(Literal {
+            self.node_at(checkpoint, SyntaxKind::Literal);
+            return Ok(Expr::Literal(Box::new(Literal {
                 value: LiteralType::Nil,
-            }));
+            })));
         }
 
         if self.match_token(vec![TokenType::Number, TokenType::String]) {
-            return Ok(Expr::Literal(Literal {
+            self.node_at(checkpoint, SyntaxKind::Literal);
+            return Ok(Expr::Literal(Box::new(Literal {
                 value: self.previous().literal,
-            }));
+            })));
+        }
+
+        if self.match_token(vec![TokenType::Identifier]) {
+            self.node_at(checkpoint, SyntaxKind::Variable);
+            return Ok(Expr::Variable(Box::new(Variable {
+                name: self.previous(),
+            })));
+        }
+
+        if self.check(&TokenType::LeftParen) && self.is_lambda() {
+            return self.lambda(checkpoint);
+        }
+
+        if !self.is_jlox() && self.match_token(vec![TokenType::Fun]) {
+            return self.fun_lambda(checkpoint);
         }
 
         if self.match_token(vec![TokenType::LeftParen]) {
+            self.nest()?;
             let comma = self.comma()?;
-            self.consume(&TokenType::RightParen, "Expect ')' after expression.")?;
-            return Ok(Expr::Grouping(Grouping {
+            self.consume(
+                &TokenType::RightParen,
+                Message::ExpectRightParenAfterExpression,
+            )?;
+            self.unnest();
+            self.node_at(checkpoint, SyntaxKind::Grouping);
+            return Ok(Expr::Grouping(Box::new(Grouping {
                 expression: Box::new(comma),
-            }));
+            })));
         }
 
-        Err(self.error(&self.peek(), "Expect expression."))
+        // There are no loops to exit yet, so these are always misplaced.
+        if self.match_token(vec![TokenType::Break, TokenType::Continue]) {
+            let keyword = self.previous();
+            return Err(self.error_handler.error_with_token(
+                &keyword,
+                Message::OutsideLoop,
+                &[&keyword.lexeme],
+            ));
+        }
+
+        Err(self.error(&self.peek(), Message::ExpectExpression))
+    }
+
+    /// Enters a production that recurses, which the caller leaves with
+    /// `unnest`.
+    fn nest(&mut self) -> Result<(), String> {
+        self.nesting += 1;
+        self.deepen()?;
+
+        if self.nesting > MAX_NESTING {
+            return Err(self.too_deep());
+        }
+
+        Ok(())
+    }
+
+    fn unnest(&mut self) {
+        self.nesting -= 1;
+        self.depth -= 1;
+    }
+
+    /// Adds a level to the tree, which the production that loops restores
+    /// when it returns.
+    fn deepen(&mut self) -> Result<(), String> {
+        self.depth += 1;
+
+        if self.depth > MAX_DEPTH {
+            return Err(self.too_deep());
+        }
+
+        Ok(())
+    }
+
+    fn too_deep(&mut self) -> String {
+        let token = self.previous();
+        self.error(&token, Message::TooMuchNesting)
+    }
+
+    /// Whether the parentheses ahead hold the parameters of a lambda rather
+    /// than a grouping, which takes looking past them for `=>`.
+    fn is_lambda(&self) -> bool {
+        let token_type = |index: usize| self.tokens.get(index).map(|token| &token.token_type);
+        let mut index = self.current + 1;
+
+        if token_type(index) != Some(&TokenType::RightParen) {
+            loop {
+                if token_type(index) != Some(&TokenType::Identifier) {
+                    return false;
+                }
+
+                index += 1;
+                match token_type(index) {
+                    Some(TokenType::Comma) => index += 1,
+                    Some(TokenType::RightParen) => break,
+                    _ => return false,
+                }
+            }
+        }
+
+        token_type(index + 1) == Some(&TokenType::Arrow)
+    }
+
+    /// Parses a lambda, whose parameter list `is_lambda` has already checked.
+    fn lambda(&mut self, checkpoint: usize) -> Result<Expr, String> {
+        self.advance();
+        let params = self.parameters()?;
+        let arrow = self.advance();
+
+        self.nest()?;
+        let body = self.expression()?;
+        self.unnest();
+        self.node_at(checkpoint, SyntaxKind::Lambda);
+
+        Ok(Expr::Lambda(Box::new(Lambda {
+            params,
+            arrow,
+            body: Box::new(body),
+        })))
+    }
+
+    /// Parses `fun (a, b) { return a + b; }`, whose `fun` has been matched.
+    /// The body is a single expression, so `return` and `;` are optional. The
+    /// `fun` stands in for the arrow.
+    fn fun_lambda(&mut self, checkpoint: usize) -> Result<Expr, String> {
+        let arrow = self.previous();
+        self.consume(&TokenType::LeftParen, Message::ExpectLeftParenAfterFun)?;
+        let params = self.parameters()?;
+        self.consume(&TokenType::LeftBrace, Message::ExpectLeftBraceBeforeBody)?;
+        self.match_token(vec![TokenType::Return]);
+
+        self.nest()?;
+        let body = self.expression()?;
+        self.unnest();
+
+        self.match_token(vec![TokenType::Semicolon]);
+        self.consume(&TokenType::RightBrace, Message::ExpectRightBraceAfterBody)?;
+        self.node_at(checkpoint, SyntaxKind::Lambda);
+
+        Ok(Expr::Lambda(Box::new(Lambda {
+            params,
+            arrow,
+            body: Box::new(body),
+        })))
+    }
+
+    /// Parses the parameter names after a lambda's `(`, and the `)`.
+    fn parameters(&mut self) -> Result<Vec<Token>, String> {
+        let mut params: Vec<Token> = Vec::new();
+
+        if !self.check(&TokenType::RightParen) {
+            loop {
+                let param = self.consume(&TokenType::Identifier, Message::ExpectParameterName)?;
+
+                if params.len() >= 255 {
+                    self.error(&param, Message::TooManyParameters);
+                }
+                if params.iter().any(|other| other.lexeme == param.lexeme) {
+                    self.error_handler.error_with_token(
+                        &param,
+                        Message::DuplicateParameter,
+                        &[&param.lexeme],
+                    );
+                }
+
+                params.push(param);
+
+                if !self.match_token(vec![TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+
+        self.consume(
+            &TokenType::RightParen,
+            Message::ExpectRightParenAfterParameters,
+        )?;
+
+        Ok(params)
     }
 
     fn match_token(&mut self, types: Vec<TokenType>) -> bool {
@@ -195,7 +559,7 @@ impl<'a> Parser<'a> {
         false
     }
 
-    fn consume(&mut self, token_type: &TokenType, message: &str) -> Result<Token, String> {
+    fn consume(&mut self, token_type: &TokenType, message: Message) -> Result<Token, String> {
         if self.check(token_type) {
             return Ok(self.advance());
         }
@@ -203,11 +567,27 @@ impl<'a> Parser<'a> {
         Err(self.error(&self.peek(), message))
     }
 
-    fn error(&mut self, token: &Token, message: &str) -> String {
-        self.error_handler.error_with_token(token, message);
-        message.to_string()
+    /// Rejects tokens left over after the expression, such as a stray ')'.
+    /// A character the scanner skipped already explains any leftovers, as in
+    /// `1 @ 2`, so those aren't reported twice. jlox ignores leftovers.
+    fn end(&mut self) -> Result<(), String> {
+        if self.is_at_end() || self.error_handler.had_error() || self.is_jlox() {
+            return Ok(());
+        }
+
+        Err(self.error(&self.peek(), Message::ExpectEndOfExpression))
+    }
+
+    /// Whether to parse only what jlox does.
+    fn is_jlox(&self) -> bool {
+        self.error_handler.locale.compat == Compat::Jlox
+    }
+
+    fn error(&mut self, token: &Token, message: Message) -> String {
+        self.error_handler.error_with_token(token, message, &[])
     }
 
+    #[allow(dead_code)]
     fn synchronize(&mut self) {
         self.advance();
 
@@ -224,7 +604,7 @@ impl<'a> Parser<'a> {
                 | TokenType::If
                 | TokenType::While
                 | TokenType::Print
-                | TokenType::Return => return (),
+                | TokenType::Return => return,
                 _ => self.advance(),
             };
         }
@@ -241,20 +621,36 @@ impl<'a> Parser<'a> {
     fn advance(&mut self) -> Token {
         if !self.is_at_end() {
             self.current += 1;
+
+            if let Some(events) = &mut self.events {
+                events.push(Event::Token);
+            }
         }
 
         self.previous()
     }
 
+    fn checkpoint(&self) -> usize {
+        self.events.as_ref().map_or(0, Vec::len)
+    }
+
+    /// Wraps everything recorded since `checkpoint` in a `kind` node.
+    fn node_at(&mut self, checkpoint: usize, kind: SyntaxKind) {
+        if let Some(events) = &mut self.events {
+            events.insert(checkpoint, Event::Start(kind));
+            events.push(Event::Finish);
+        }
+    }
+
     fn is_at_end(&self) -> bool {
         self.peek().token_type == TokenType::EOF
     }
 
     fn peek(&self) -> Token {
-        self.tokens.iter().nth(self.current).unwrap().clone()
+        self.tokens.get(self.current).unwrap().clone()
     }
 
     fn previous(&mut self) -> Token {
-        self.tokens.iter().nth(self.current - 1).unwrap().clone()
+        self.tokens.get(self.current - 1).unwrap().clone()
     }
 }
@@ -1,86 +1,258 @@
 use std::{
     fs,
-    io::{self, BufRead, BufReader, Write},
+    io::{self, BufRead, BufReader, Read, Write},
     process,
 };
 
-use crate::{
-    ast_printer::AstPrinter, error_handler::ErrorHandler, interpreter::Interpreter, parser::Parser,
-    scanner::Scanner,
+use interpreter::{
+    Compat, Diagnostic, DiagnosticKind, Dump, Language, Limits, Lint, Lints, Locale, Lox, Message,
+    exit_code, interrupt, serve_dap, serve_lsp,
 };
 
+pub enum Input {
+    Prompt,
+    File(String),
+    Stdin,
+    Eval(String),
+}
+
 pub struct Program {
-    error_handler: ErrorHandler,
+    lox: Lox,
+    dump: Option<Dump>,
+    locale: Locale,
+    lints: Lints,
+    limits: Limits,
 }
 
 impl Program {
-    pub fn new() -> Self {
-        let error_handler = ErrorHandler::new();
+    pub fn new(
+        dump: Option<Dump>,
+        script_args: Vec<String>,
+        compat: Compat,
+        language: Language,
+        lints: Lints,
+        limits: Limits,
+    ) -> Self {
+        let locale = Locale::new(language, compat);
 
-        Self { error_handler }
+        Self {
+            lox: Program::lox(script_args, locale, lints, limits),
+            dump,
+            locale,
+            lints,
+            limits,
+        }
     }
 
-    pub fn init(&mut self, file: Option<String>) {
-        if let Some(script) = file {
-            self.run_file(&script);
-        } else {
-            self.run_prompt();
+    fn lox(script_args: Vec<String>, locale: Locale, lints: Lints, limits: Limits) -> Lox {
+        let mut lox = Lox::with_args(script_args);
+        lox.enable_process_natives();
+        lox.set_compat(locale.compat);
+        lox.set_language(locale.language);
+        lox.set_limits(limits);
+
+        for lint in Lint::ALL {
+            lox.set_lint_level(lint, lints.level(lint));
         }
+
+        lox
     }
 
-    fn run_file(&mut self, path: &str) {
-        let content =
-            fs::read_to_string(path).expect("Erro ao ler o arquivo! Informe um caminho válido.");
+    pub fn init(&mut self, input: Input) {
+        interrupt::install();
+
+        match input {
+            Input::Prompt => self.run_prompt(),
+            Input::File(path) => self.run_file(&path),
+            Input::Stdin => self.run_stdin(),
+            Input::Eval(code) => self.run_script(&code),
+        }
+    }
+
+    /// Formats each script in place, or with `check` only lists the ones that
+    /// aren't formatted. The path `-` formats stdin to stdout.
+    pub fn format(&self, paths: &[String], check: bool) {
+        let mut exit_code = 0;
+
+        for path in paths {
+            let source = if path == "-" {
+                let mut content = String::new();
+                io::stdin().read_to_string(&mut content).map(|_| content)
+            } else {
+                fs::read_to_string(path)
+            };
+
+            let Ok(source) = source else {
+                eprintln!("{}", self.locale.text(Message::FileReadError, &[]));
+                exit_code = exit_code.max(66);
+                continue;
+            };
+
+            match self.lox.format(&source) {
+                Ok(formatted) if check => {
+                    if formatted != source {
+                        println!("{}", self.locale.text(Message::NotFormatted, &[path]));
+                        exit_code = exit_code.max(1);
+                    }
+                }
+                Ok(formatted) if path == "-" => print!("{}", formatted),
+                Ok(formatted) => {
+                    if formatted == source {
+                        continue;
+                    }
+
+                    if let Err(err) = fs::write(path, formatted) {
+                        let message = self
+                            .locale
+                            .text(Message::FileWriteError, &[path, &err.to_string()]);
+                        eprintln!("{}", message);
+                        exit_code = exit_code.max(74);
+                    }
+                }
+                Err(diagnostics) => {
+                    for diagnostic in diagnostics {
+                        eprintln!("{}", diagnostic.render(self.locale));
+                    }
+                    exit_code = exit_code.max(65);
+                }
+            }
+        }
+
+        process::exit(exit_code);
+    }
+
+    /// Serves the Debug Adapter Protocol on stdin and stdout.
+    pub fn dap(&self) {
+        let new_lox = |script_args| Program::lox(script_args, self.locale, self.lints, self.limits);
+
+        if let Err(err) = serve_dap(self.locale, new_lox, io::stdin().lock(), io::stdout()) {
+            let message = self
+                .locale
+                .text(Message::LineReadError, &[&err.to_string()]);
+            eprintln!("{}", message);
+            process::exit(74);
+        }
+    }
+
+    /// Serves the Language Server Protocol on stdin and stdout.
+    pub fn lsp(&self) {
+        match serve_lsp(&self.lox, io::stdin().lock(), io::stdout().lock()) {
+            Ok(exit_code) => process::exit(exit_code),
+            Err(err) => {
+                let message = self
+                    .locale
+                    .text(Message::LineReadError, &[&err.to_string()]);
+                eprintln!("{}", message);
+                process::exit(74);
+            }
+        }
+    }
 
-        if self.error_handler.had_error {
-            process::exit(65);
+    fn run_file(&mut self, path: &str) {
+        match fs::read_to_string(path) {
+            Ok(content) => {
+                self.lox.set_file(Some(path));
+                self.run_script(&content);
+            }
+            Err(_) => {
+                eprintln!("{}", self.locale.text(Message::FileReadError, &[]));
+                process::exit(66);
+            }
         }
+    }
 
-        if self.error_handler.had_runtime_error {
-            process::exit(70);
+    fn run_stdin(&mut self) {
+        let mut content = String::new();
+        if io::stdin().read_to_string(&mut content).is_err() {
+            eprintln!("{}", self.locale.text(Message::StdinReadError, &[]));
+            process::exit(66);
         }
 
-        self.run(&content);
+        self.run_script(&content);
+    }
+
+    /// Runs the script at `path` under the interactive debugger, which reads
+    /// its commands from stdin.
+    pub fn debug(&mut self, path: &str) {
+        let Ok(source) = fs::read_to_string(path) else {
+            eprintln!("{}", self.locale.text(Message::FileReadError, &[]));
+            process::exit(66);
+        };
+
+        self.lox.set_file(Some(path));
+        let diagnostics = self
+            .lox
+            .run_debugger(&source, io::stdin().lock(), io::stdout());
+
+        Program::exit_on_error(&diagnostics);
+    }
+
+    fn run_script(&mut self, source: &str) {
+        let diagnostics = self.run(source);
+        Program::exit_on_error(&diagnostics);
+    }
+
+    fn exit_on_error(diagnostics: &[Diagnostic]) {
+        match exit_code(diagnostics) {
+            0 => {}
+            exit_code => process::exit(exit_code),
+        }
     }
 
     fn run_prompt(&mut self) {
+        const PROMPT: &str = "> ";
+
         let stdin = io::stdin();
         let handle = stdin.lock();
 
-        let reader = BufReader::new(handle);
+        let mut lines = BufReader::new(handle).lines();
+        interrupt::watch_prompt(PROMPT);
+
+        loop {
+            // Prompting before the prompt shows lets Ctrl-C redraw it as soon
+            // as it can be seen.
+            interrupt::set_prompting(true);
+            print!("{}", PROMPT);
+            io::stdout().flush().unwrap();
 
-        print!("> ");
-        io::stdout().flush().unwrap();
+            let line = lines.next();
+            interrupt::set_prompting(false);
 
-        for line in reader.lines() {
             match line {
-                Ok(text) => {
-                    self.run(&text);
+                Some(Ok(text)) => {
+                    interrupt::reset();
+                    let diagnostics = self.run(&text);
+
+                    for diagnostic in diagnostics {
+                        if let DiagnosticKind::Exit(code) = diagnostic.kind {
+                            process::exit(code);
+                        }
+                    }
                 }
-                Err(e) => eprintln!("Error reading line: {}", e),
+                Some(Err(e)) => eprintln!(
+                    "{}",
+                    self.locale.text(Message::LineReadError, &[&e.to_string()])
+                ),
+                None => break,
             }
+        }
+    }
 
-            print!("> ");
-            io::stdout().flush().unwrap();
-            self.error_handler.had_error = false;
+    fn run(&mut self, source: &str) -> Vec<Diagnostic> {
+        match self.dump {
+            Some(dump) => self.dump(dump, source),
+            None => self.lox.run_source(source),
         }
     }
 
-    fn run(&mut self, source: &str) {
-        let mut scanner = Scanner::new(source.to_string(), &mut self.error_handler);
-        let tokens = scanner.scan_tokens();
-        let mut parser = Parser::new(tokens.to_owned(), &mut self.error_handler);
-        let expression = parser.parse();
+    fn dump(&self, dump: Dump, source: &str) -> Vec<Diagnostic> {
+        let (output, diagnostics) = self.lox.dump(dump, source);
+        print!("{}", output);
 
-        if self.error_handler.had_error {
-            return;
+        for diagnostic in &diagnostics {
+            eprintln!("{}", diagnostic.render(self.locale));
         }
 
-        let mut interpreter = Interpreter::new(&mut self.error_handler);
-
-        if let Some(expr) = expression {
-            // AstPrinter::print(expr);
-            interpreter.interpret(expr);
-        };
+        diagnostics
     }
 }
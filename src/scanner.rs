@@ -1,6 +1,8 @@
 use crate::{
+    compat::Compat,
     error_handler::ErrorHandler,
-    token::{LiteralType, Token},
+    messages::Message,
+    token::{LiteralType, Token, Trivia, TriviaKind},
     token_type::TokenType,
 };
 
@@ -10,13 +12,29 @@ pub struct Scanner<'a> {
     start: usize,
     current: usize,
     line: usize,
+    line_start: usize,
+    column: usize,
+    lossless: bool,
+    /// Trivia waiting for the next token.
+    trivia: Vec<Trivia>,
+    /// Whether new trivia still belongs to the last token's line.
+    trailing: bool,
     error_handler: &'a mut ErrorHandler,
 }
 
+/// Every reserved word, as `keywords` recognizes them.
+pub const KEYWORDS: [&str; 19] = [
+    "and", "break", "class", "continue", "div", "else", "false", "for", "fun", "if", "nil", "or",
+    "print", "return", "super", "this", "true", "var", "while",
+];
+
 fn keywords(key: &str) -> TokenType {
     match key {
         "and" => TokenType::And,
+        "break" => TokenType::Break,
         "class" => TokenType::Class,
+        "continue" => TokenType::Continue,
+        "div" => TokenType::Div,
         "else" => TokenType::Else,
         "false" => TokenType::False,
         "for" => TokenType::For,
@@ -43,22 +61,42 @@ impl<'a> Scanner<'a> {
             start: 0,
             current: 0,
             line: 1,
+            line_start: 0,
+            column: 1,
+            lossless: false,
+            trivia: Vec::new(),
+            trailing: false,
             error_handler,
         }
     }
 
+    /// A scanner whose tokens keep the whitespace and comments around them,
+    /// so the source can be rebuilt from the tokens byte for byte.
+    pub fn lossless(source: String, error_handler: &'a mut ErrorHandler) -> Self {
+        Self {
+            lossless: true,
+            ..Self::new(source, error_handler)
+        }
+    }
+
     pub fn scan_tokens(&mut self) -> &Vec<Token> {
+        self.skip_shebang();
+
         while !self.is_at_end() {
             self.start = self.current;
+            self.column = self.current - self.line_start + 1;
             self.scan_token();
         }
 
-        self.tokens.push(Token::new(
+        let mut eof = Token::new(
             TokenType::EOF,
             String::from(""),
             LiteralType::None,
             self.line,
-        ));
+            self.current - self.line_start + 1,
+        );
+        eof.leading_trivia = std::mem::take(&mut self.trivia);
+        self.tokens.push(eof);
 
         &self.tokens
     }
@@ -73,10 +111,51 @@ impl<'a> Scanner<'a> {
             '}' => self.add_token(TokenType::RightBrace, LiteralType::None),
             ',' => self.add_token(TokenType::Comma, LiteralType::None),
             '.' => self.add_token(TokenType::Dot, LiteralType::None),
-            '-' => self.add_token(TokenType::Minus, LiteralType::None),
-            '+' => self.add_token(TokenType::Plus, LiteralType::None),
+            '-' => {
+                let token_type = if self.match_extension('=') {
+                    TokenType::MinusEqual
+                } else if self.match_extension('-') {
+                    TokenType::MinusMinus
+                } else {
+                    TokenType::Minus
+                };
+                self.add_token(token_type, LiteralType::None)
+            }
+            '+' => {
+                let token_type = if self.match_extension('=') {
+                    TokenType::PlusEqual
+                } else if self.match_extension('+') {
+                    TokenType::PlusPlus
+                } else {
+                    TokenType::Plus
+                };
+                self.add_token(token_type, LiteralType::None)
+            }
             ';' => self.add_token(TokenType::Semicolon, LiteralType::None),
-            '*' => self.add_token(TokenType::Star, LiteralType::None),
+            '*' => {
+                let token_type = if self.match_extension('*') {
+                    TokenType::StarStar
+                } else if self.match_extension('=') {
+                    TokenType::StarEqual
+                } else {
+                    TokenType::Star
+                };
+                self.add_token(token_type, LiteralType::None)
+            }
+            '%' if self.is_jlox() => self.unexpected_character(char),
+            '%' => {
+                let token_type = if self.match_char('=') {
+                    TokenType::PercentEqual
+                } else {
+                    TokenType::Percent
+                };
+                self.add_token(token_type, LiteralType::None)
+            }
+            '&' | '|' | '^' | '~' | '?' | ':' if self.is_jlox() => self.unexpected_character(char),
+            '&' => self.add_token(TokenType::Ampersand, LiteralType::None),
+            '|' => self.add_token(TokenType::Pipe, LiteralType::None),
+            '^' => self.add_token(TokenType::Caret, LiteralType::None),
+            '~' => self.add_token(TokenType::Tilde, LiteralType::None),
             ':' => self.add_token(TokenType::Colon, LiteralType::None),
             '?' => self.add_token(TokenType::Question, LiteralType::None),
             '!' => {
@@ -91,66 +170,90 @@ impl<'a> Scanner<'a> {
                 )
             }
             '=' => {
-                let next_is_equal = self.match_char('=');
-                self.add_token(
-                    if next_is_equal {
-                        TokenType::EqualEqual
-                    } else {
-                        TokenType::Equal
-                    },
-                    LiteralType::None,
-                )
+                let token_type = if self.match_char('=') {
+                    TokenType::EqualEqual
+                } else if self.match_extension('>') {
+                    TokenType::Arrow
+                } else {
+                    TokenType::Equal
+                };
+                self.add_token(token_type, LiteralType::None)
             }
             '<' => {
-                let next_is_equal = self.match_char('=');
-                self.add_token(
-                    if next_is_equal {
-                        TokenType::LessEqual
-                    } else {
-                        TokenType::Less
-                    },
-                    LiteralType::None,
-                )
+                let token_type = if self.match_char('=') {
+                    TokenType::LessEqual
+                } else if self.match_extension('<') {
+                    TokenType::LessLess
+                } else {
+                    TokenType::Less
+                };
+                self.add_token(token_type, LiteralType::None)
             }
             '>' => {
-                let next_is_equal = self.match_char('=');
-                self.add_token(
-                    if next_is_equal {
-                        TokenType::GreaterEqual
-                    } else {
-                        TokenType::Greater
-                    },
-                    LiteralType::None,
-                )
+                let token_type = if self.match_char('=') {
+                    TokenType::GreaterEqual
+                } else if self.match_extension('>') {
+                    TokenType::GreaterGreater
+                } else {
+                    TokenType::Greater
+                };
+                self.add_token(token_type, LiteralType::None)
             }
             '/' => {
                 if self.match_char('/') {
                     while self.peek() != '\n' && !self.is_at_end() {
                         self.advance();
                     }
-                } else if self.match_char('*') {
-                    println!("Multiline comment");
+
+                    let comment = self.source[self.start + 2..self.current].to_string();
+                    self.error_handler.pragma(self.line, &comment);
+                    self.add_trivia(TriviaKind::LineComment);
+                } else if self.match_extension('*') {
                     self.ignore_multiline_comment();
+                    self.add_trivia(TriviaKind::BlockComment);
+                } else if self.match_extension('=') {
+                    self.add_token(TokenType::SlashEqual, LiteralType::None);
                 } else {
                     self.add_token(TokenType::Slash, LiteralType::None);
                 }
             }
             '"' => self.add_string(),
-            ' ' | '\r' | '\t' => {}
-            '\n' => self.line += 1,
+            ' ' | '\r' | '\t' => self.add_trivia(TriviaKind::Whitespace),
+            '\n' => {
+                self.line += 1;
+                self.line_start = self.current;
+                self.add_trivia(TriviaKind::Newline);
+            }
             c => {
                 if c.is_ascii_digit() {
                     self.add_number();
                 } else if self.is_alpha(c) {
                     self.add_identifier();
                 } else {
-                    self.error_handler
-                        .error(self.line, &format!("Unexpected character. {}", c));
+                    self.unexpected_character(c);
                 }
             }
         }
     }
 
+    fn unexpected_character(&mut self, c: char) {
+        self.error_handler
+            .error(self.line, Message::UnexpectedCharacter, &[&c.to_string()]);
+        self.add_trivia(TriviaKind::Skipped);
+    }
+
+    fn skip_shebang(&mut self) {
+        if self.is_jlox() || !self.source.starts_with("#!") {
+            return;
+        }
+
+        while self.peek() != '\n' && !self.is_at_end() {
+            self.advance();
+        }
+
+        self.add_trivia(TriviaKind::Shebang);
+    }
+
     fn ignore_multiline_comment(&mut self) {
         let mut nested_comments = 0;
 
@@ -161,6 +264,7 @@ impl<'a> Scanner<'a> {
 
             if self.peek() == '\n' {
                 self.line += 1;
+                self.line_start = self.current + 1;
             };
 
             self.advance();
@@ -174,7 +278,7 @@ impl<'a> Scanner<'a> {
 
         if self.is_at_end() {
             self.error_handler
-                .error(self.line, "Unterminated comment block");
+                .error(self.line, Message::UnterminatedComment, &[]);
             return;
         }
 
@@ -187,11 +291,23 @@ impl<'a> Scanner<'a> {
             self.advance();
         }
 
-        let token_type = keywords(&self.source[self.start..self.current]);
+        let token_type = match keywords(&self.source[self.start..self.current]) {
+            // jlox reserves none of these, so they name variables there.
+            TokenType::Break | TokenType::Continue | TokenType::Div if self.is_jlox() => {
+                TokenType::Identifier
+            }
+            token_type => token_type,
+        };
 
         self.add_token(token_type, LiteralType::None);
     }
 
+    /// Whether to scan only what jlox does, leaving rlox's additions to the
+    /// language out.
+    fn is_jlox(&self) -> bool {
+        self.error_handler.locale.compat == Compat::Jlox
+    }
+
     fn is_alpha(&self, c: char) -> bool {
         c.is_ascii_alphabetic() || c == '_'
     }
@@ -221,17 +337,19 @@ impl<'a> Scanner<'a> {
         )
     }
 
-    // !TODO: resolver problema com acentos quando parseia a string!
     fn add_string(&mut self) {
         while self.peek() != '"' && !self.is_at_end() {
             if self.peek() == '\n' {
                 self.line += 1;
+                self.line_start = self.current + 1;
             };
             self.advance();
         }
 
         if self.is_at_end() {
-            self.error_handler.error(self.line, "Unterminated string");
+            self.error_handler
+                .error(self.line, Message::UnterminatedString, &[]);
+            self.add_trivia(TriviaKind::Skipped);
             return;
         }
 
@@ -244,21 +362,59 @@ impl<'a> Scanner<'a> {
         self.add_token(TokenType::String, LiteralType::String(value))
     }
 
+    fn add_trivia(&mut self, kind: TriviaKind) {
+        if !self.lossless {
+            return;
+        }
+
+        if kind == TriviaKind::Newline {
+            self.trailing = false;
+        }
+
+        let text = &self.source[self.start..self.current];
+        let trivia = match (self.trailing, self.tokens.last_mut()) {
+            (true, Some(token)) => &mut token.trailing_trivia,
+            _ => &mut self.trivia,
+        };
+
+        match trivia.last_mut() {
+            Some(last) if last.kind == kind && kind == TriviaKind::Whitespace => {
+                last.text.push_str(text)
+            }
+            _ => trivia.push(Trivia {
+                kind,
+                text: text.to_string(),
+            }),
+        }
+    }
+
     fn add_token(&mut self, token_type: TokenType, literal: LiteralType) {
         let text = &self.source[self.start..self.current];
-        self.tokens
-            .push(Token::new(token_type, text.to_string(), literal, self.line))
+        let mut token = Token::new(
+            token_type,
+            text.to_string(),
+            literal,
+            self.line,
+            self.column,
+        );
+
+        if self.lossless {
+            token.leading_trivia = std::mem::take(&mut self.trivia);
+            self.trailing = true;
+        }
+
+        self.tokens.push(token)
     }
 
     fn advance(&mut self) -> Option<char> {
-        let char = self.source.chars().nth(self.current);
-        self.current += 1;
+        let char = self.source[self.current..].chars().next();
+        self.current += char.map_or(1, char::len_utf8);
 
         char
     }
 
     fn is_at_end(&self) -> bool {
-        return self.current >= self.source.len();
+        self.current >= self.source.len()
     }
 
     fn match_char(&mut self, expected: char) -> bool {
@@ -270,23 +426,29 @@ impl<'a> Scanner<'a> {
             return false;
         }
 
-        self.current += 1;
+        self.current += expected.len_utf8();
         true
     }
 
+    /// Like `match_char`, for the second character of an operator jlox
+    /// doesn't have, which scans as two tokens there.
+    fn match_extension(&mut self, expected: char) -> bool {
+        !self.is_jlox() && self.match_char(expected)
+    }
+
     fn peek(&self) -> char {
         if self.is_at_end() {
             return '\0';
         }
 
-        self.source.chars().nth(self.current).unwrap()
+        self.source[self.current..].chars().next().unwrap()
     }
 
     fn peek_next(&self) -> char {
-        if self.current + 1 >= self.source.len() {
+        if self.is_at_end() {
             return '\0';
         }
 
-        self.source.chars().nth(self.current + 1).unwrap()
+        self.source[self.current..].chars().nth(1).unwrap_or('\0')
     }
 }
@@ -1,6 +1,6 @@
 use std::fmt::{self, Display};
 
-use crate::{error_handler::RuntimeError, token_type::TokenType};
+use crate::{function::Function, native::NativeFunction, token_type::TokenType};
 
 #[derive(PartialEq, Clone, Debug)]
 pub enum LiteralType {
@@ -9,16 +9,8 @@ pub enum LiteralType {
     Bool(bool),
     Nil,
     None,
-}
-
-impl LiteralType {
-    pub fn as_number(&self, token: Token) -> Result<f64, RuntimeError> {
-        if let LiteralType::Number(value) = self {
-            return Ok(*value);
-        } else {
-            return Err(RuntimeError::new(token, "Operand must be a number"));
-        };
-    }
+    NativeFunction(NativeFunction),
+    Function(Function),
 }
 
 impl Display for LiteralType {
@@ -29,27 +21,69 @@ impl Display for LiteralType {
             LiteralType::Bool(b) => write!(f, "{}", b),
             LiteralType::Nil => write!(f, "Nil"),
             LiteralType::None => write!(f, "None"),
+            LiteralType::NativeFunction(_) => write!(f, "<native fn>"),
+            LiteralType::Function(function) => write!(f, "{}", function),
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriviaKind {
+    Whitespace,
+    Newline,
+    LineComment,
+    BlockComment,
+    Shebang,
+    /// Text the scanner rejected, such as an unexpected character.
+    Skipped,
+}
+
+/// Source text between tokens, kept only by a lossless scan.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub text: String,
+}
+
 #[derive(PartialEq, Clone, Debug)]
 pub struct Token {
     pub token_type: TokenType,
     pub lexeme: String,
     pub literal: LiteralType,
     pub line: usize,
+    pub column: usize,
+    /// Trivia since the end of the previous line, or the previous token's
+    /// trailing trivia.
+    pub leading_trivia: Vec<Trivia>,
+    /// Trivia up to the end of the line the token ends on.
+    pub trailing_trivia: Vec<Trivia>,
 }
 
 impl Token {
-    pub fn new(token_type: TokenType, lexeme: String, literal: LiteralType, line: usize) -> Self {
+    pub fn new(
+        token_type: TokenType,
+        lexeme: String,
+        literal: LiteralType,
+        line: usize,
+        column: usize,
+    ) -> Self {
         Token {
             token_type,
             lexeme,
             literal,
             line,
+            column,
+            leading_trivia: Vec::new(),
+            trailing_trivia: Vec::new(),
         }
     }
+
+    /// The token as it appeared in the source, trivia included.
+    pub fn full_text(&self) -> String {
+        let trivia = |trivia: &[Trivia]| trivia.iter().map(|t| t.text.as_str()).collect::<String>();
+
+        trivia(&self.leading_trivia) + &self.lexeme + &trivia(&self.trailing_trivia)
+    }
 }
 
 impl fmt::Display for Token {
@@ -1,3 +1,4 @@
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, PartialEq, Clone)]
 pub enum TokenType {
     // Single-character tokens
@@ -12,18 +13,34 @@ pub enum TokenType {
     Semicolon,
     Slash,
     Star,
+    Percent,
+    Ampersand,
+    Pipe,
+    Caret,
+    Tilde,
     Question,
     Colon,
 
     // One or two character tokens
+    PlusEqual,
+    PlusPlus,
+    MinusEqual,
+    MinusMinus,
+    StarEqual,
+    SlashEqual,
+    PercentEqual,
     Bang,
     BangEqual,
     Equal,
     EqualEqual,
+    Arrow,
+    StarStar,
     Greater,
     GreaterEqual,
+    GreaterGreater,
     Less,
     LessEqual,
+    LessLess,
 
     // Literals
     Identifier,
@@ -32,7 +49,10 @@ pub enum TokenType {
 
     // Keywords
     And,
+    Break,
     Class,
+    Continue,
+    Div,
     Else,
     False,
     Fun,
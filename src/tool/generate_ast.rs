@@ -12,6 +12,11 @@ pub fn generate_ast(output_dir: &str) -> io::Result<()> {
             "Unary    = operator: Token, right: Box<Expr>",
             "Comma    = left: Box<Expr>, right: Box<Expr>",
             "Ternary  = condition: Box<Expr>, then_branch: Box<Expr>, else_branch: Box<Expr>",
+            "Call     = callee: Box<Expr>, paren: Token, arguments: Vec<Expr>",
+            "Variable = name: Token",
+            "Lambda   = params: Vec<Token>, arrow: Token, body: Box<Expr>",
+            "Assign   = name: Token, operator: Token, value: Box<Expr>",
+            "Increment = name: Token, operator: Token, prefix: bool",
         ],
     )?;
 
@@ -23,7 +28,7 @@ fn define_ast(output_dir: &str, base_name: &str, types: &[&str]) -> io::Result<(
     let mut file = File::create(&path)?;
 
     writeln!(file, "use crate::token::{{LiteralType, Token}};")?;
-    writeln!(file, "")?;
+    writeln!(file)?;
 
     for type_str in types {
         let parts: Vec<&str> = type_str.split('=').collect();
@@ -32,26 +37,26 @@ fn define_ast(output_dir: &str, base_name: &str, types: &[&str]) -> io::Result<(
         define_type(&mut file, class_name, fields)?;
     }
 
-    writeln!(file, "#[derive(Debug)]")?;
+    writeln!(file, "#[derive(Debug, Clone)]")?;
     writeln!(file, "pub enum {} {{", base_name)?;
     for type_str in types {
         let parts: Vec<&str> = type_str.split('=').collect();
         let class_name = parts[0].trim();
 
-        writeln!(file, "    {}({}),", class_name, class_name)?;
+        writeln!(file, "    {}(Box<{}>),", class_name, class_name)?;
     }
 
     writeln!(file, "}}\n")?;
 
     writeln!(file, "pub trait Visitor<T> {{")?;
-    writeln!(file, "    fn visit(expr: {}) -> T;", base_name)?;
+    writeln!(file, "    fn visit(&mut self, expr: {}) -> T;", base_name)?;
     writeln!(file, "}}")?;
 
     Ok(())
 }
 
 fn define_type(file: &mut File, class_name: &str, field_list: &str) -> io::Result<()> {
-    writeln!(file, "#[derive(Debug)]")?;
+    writeln!(file, "#[derive(Debug, Clone)]")?;
     writeln!(file, "pub struct {} {{", class_name)?;
 
     field_list.split(", ").for_each(|field| {
@@ -0,0 +1,20 @@
+use std::{env, fs, process::Command};
+
+#[test]
+fn flags_after_the_script_are_its_arguments() {
+    let path = env::temp_dir().join(format!("rlox-cli-{}.lox", std::process::id()));
+    fs::write(&path, "args()").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_interpreter"))
+        .arg(&path)
+        .args(["--tokens", "-e", "1"])
+        .output()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "String(\"--tokens -e 1\")\n"
+    );
+}
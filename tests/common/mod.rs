@@ -0,0 +1,74 @@
+//! Helpers shared by the integration tests. Each test crate uses a different
+//! subset of them.
+#![allow(dead_code)]
+
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use serde_json::Value;
+
+/// Every `.lox` file under `dir`, in a stable order.
+pub fn collect_scripts(dir: &Path) -> Vec<PathBuf> {
+    let mut entries = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    entries.sort();
+
+    let mut scripts = Vec::new();
+    for path in entries {
+        if path.is_dir() {
+            scripts.extend(collect_scripts(&path));
+        } else if path.extension().is_some_and(|ext| ext == "lox") {
+            scripts.push(path);
+        }
+    }
+
+    scripts
+}
+
+/// Prefixes `message` with the `Content-Length` header that both the
+/// Language Server and the Debug Adapter protocols use.
+pub fn frame(message: Value) -> String {
+    let body = message.to_string();
+    format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+}
+
+/// Sends the framed `messages` to `interpreter <subcommand>`, then returns
+/// every message it wrote back and its exit code.
+pub fn session(subcommand: &str, messages: &[String]) -> (Vec<Value>, i32) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_interpreter"))
+        .arg(subcommand)
+        .env_remove("LANG")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(messages.concat().as_bytes())
+        .unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    let mut stdout = String::from_utf8(output.stdout).unwrap();
+    let mut replies = Vec::new();
+
+    while let Some((header, rest)) = stdout.split_once("\r\n\r\n") {
+        let length: usize = header
+            .strip_prefix("Content-Length: ")
+            .unwrap()
+            .parse()
+            .unwrap();
+        replies.push(serde_json::from_str(&rest[..length]).unwrap());
+        stdout = rest[length..].to_string();
+    }
+
+    (replies, output.status.code().unwrap())
+}
@@ -0,0 +1,194 @@
+mod common;
+
+use std::{env, fs};
+
+use serde_json::{Value, json};
+
+use common::frame;
+
+const SCRIPT: &str = "\"x is \" +\n  (x + 1 == clock() * 0 + 43 ? \"set\" : \"unset\")\n";
+
+fn request(seq: u64, command: &str, arguments: Value) -> String {
+    frame(json!({ "seq": seq, "type": "request", "command": command, "arguments": arguments }))
+}
+
+/// Sends the recorded requests to `interpreter dap` and returns everything
+/// it wrote back.
+fn session(requests: &[String]) -> Vec<Value> {
+    let (messages, exit_code) = common::session("dap", requests);
+    assert_eq!(exit_code, 0);
+
+    messages
+}
+
+fn response(messages: &[Value], seq: u64) -> &Value {
+    messages
+        .iter()
+        .find(|message| message["type"] == "response" && message["request_seq"] == seq)
+        .unwrap_or_else(|| panic!("no response to request {}", seq))
+}
+
+fn events<'a>(messages: &'a [Value], event: &str) -> Vec<&'a Value> {
+    messages
+        .iter()
+        .filter(|message| message["event"] == event)
+        .map(|message| &message["body"])
+        .collect()
+}
+
+#[test]
+fn recorded_session() {
+    let path = env::temp_dir().join(format!("rlox-dap-{}.lox", std::process::id()));
+    fs::write(&path, SCRIPT).unwrap();
+    let program = path.to_str().unwrap();
+
+    let messages = session(&[
+        request(1, "initialize", json!({ "adapterID": "rlox" })),
+        request(2, "launch", json!({ "program": program })),
+        request(
+            3,
+            "setBreakpoints",
+            json!({ "source": { "path": program }, "breakpoints": [{ "line": 2 }] }),
+        ),
+        request(4, "configurationDone", json!({})),
+        request(5, "threads", json!({})),
+        request(6, "stackTrace", json!({ "threadId": 1 })),
+        request(7, "scopes", json!({ "frameId": 0 })),
+        request(
+            8,
+            "setVariable",
+            json!({ "variablesReference": 1, "name": "x", "value": "41 + 1" }),
+        ),
+        request(9, "variables", json!({ "variablesReference": 1 })),
+        request(10, "stepIn", json!({ "threadId": 1 })),
+        request(11, "stackTrace", json!({ "threadId": 1 })),
+        request(12, "next", json!({ "threadId": 1 })),
+        request(13, "stepOut", json!({ "threadId": 1 })),
+        request(14, "disconnect", json!({})),
+    ]);
+    fs::remove_file(&path).unwrap();
+
+    for seq in 1..=14 {
+        assert_eq!(response(&messages, seq)["success"], true, "request {}", seq);
+    }
+
+    let stopped: Vec<&Value> = events(&messages, "stopped")
+        .into_iter()
+        .map(|body| &body["reason"])
+        .collect();
+    assert_eq!(stopped, ["breakpoint", "step", "step"]);
+
+    let frames = &response(&messages, 6)["body"]["stackFrames"];
+    assert_eq!(
+        frames[0],
+        json!({
+            "id": 0,
+            "name": "(== (+ x 1) (+ (* (call clock) 0) 43))",
+            "line": 2,
+            "column": 10,
+            "source": { "path": program },
+        })
+    );
+    assert_eq!(frames[1]["line"], 1);
+    let scopes = &response(&messages, 7)["body"]["scopes"];
+    assert_eq!(scopes[0]["name"], "Locals");
+    assert_eq!(scopes[1]["name"], "Globals");
+    assert_eq!(response(&messages, 8)["body"]["value"], "Number(42.0)");
+
+    let variables = response(&messages, 9)["body"]["variables"]
+        .as_array()
+        .unwrap();
+    assert!(
+        variables
+            .contains(&json!({ "name": "x", "value": "Number(42.0)", "variablesReference": 0 }))
+    );
+
+    let frames = &response(&messages, 11)["body"]["stackFrames"];
+    assert_eq!(frames[0]["name"], "(+ x 1)");
+    assert_eq!(response(&messages, 11)["body"]["totalFrames"], 3);
+
+    let output: Vec<&Value> = events(&messages, "output")
+        .into_iter()
+        .map(|body| &body["output"])
+        .collect();
+    assert_eq!(output, ["String(\"x is set\")\n"]);
+    assert_eq!(events(&messages, "exited"), [&json!({ "exitCode": 0 })]);
+    assert_eq!(events(&messages, "terminated").len(), 1);
+}
+
+#[test]
+fn locals_scope() {
+    let path = env::temp_dir().join(format!("rlox-dap-locals-{}.lox", std::process::id()));
+    fs::write(&path, "((n) =>\n  n + 1\n)(1)\n").unwrap();
+    let program = path.to_str().unwrap();
+
+    let messages = session(&[
+        request(1, "launch", json!({ "program": program })),
+        request(
+            2,
+            "setBreakpoints",
+            json!({ "source": { "path": program }, "breakpoints": [{ "line": 2 }] }),
+        ),
+        request(3, "configurationDone", json!({})),
+        request(4, "variables", json!({ "variablesReference": 2 })),
+        request(
+            5,
+            "setVariable",
+            json!({ "variablesReference": 2, "name": "n", "value": "41" }),
+        ),
+        request(6, "continue", json!({ "threadId": 1 })),
+        request(7, "disconnect", json!({})),
+    ]);
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        response(&messages, 4)["body"]["variables"],
+        json!([{ "name": "n", "value": "Number(1.0)", "variablesReference": 0 }])
+    );
+
+    let output: Vec<&Value> = events(&messages, "output")
+        .into_iter()
+        .map(|body| &body["output"])
+        .collect();
+    assert_eq!(output, ["Number(42.0)\n"]);
+}
+
+#[test]
+fn set_variable_to_exit() {
+    let path = env::temp_dir().join(format!("rlox-dap-exit-{}.lox", std::process::id()));
+    fs::write(&path, SCRIPT).unwrap();
+    let program = path.to_str().unwrap();
+
+    let messages = session(&[
+        request(1, "launch", json!({ "program": program })),
+        request(
+            2,
+            "setBreakpoints",
+            json!({ "source": { "path": program }, "breakpoints": [{ "line": 2 }] }),
+        ),
+        request(3, "configurationDone", json!({})),
+        request(
+            4,
+            "setVariable",
+            json!({ "variablesReference": 1, "name": "x", "value": "exit(1)" }),
+        ),
+        request(5, "disconnect", json!({})),
+    ]);
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(response(&messages, 4)["success"], false);
+    assert_eq!(response(&messages, 5)["success"], true);
+}
+
+#[test]
+fn requests_need_a_paused_script() {
+    let messages = session(&[
+        request(1, "stackTrace", json!({ "threadId": 1 })),
+        request(2, "configurationDone", json!({})),
+        request(3, "disconnect", json!({})),
+    ]);
+
+    assert_eq!(response(&messages, 1)["success"], false);
+    assert_eq!(response(&messages, 2)["success"], false);
+    assert_eq!(response(&messages, 3)["success"], true);
+}
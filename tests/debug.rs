@@ -0,0 +1,124 @@
+use std::{
+    env, fs,
+    io::Write,
+    process::{Command, Stdio},
+};
+
+const SCRIPT: &str = "x == 1 ?\n  args() + \" \" + env(\"DEBUG_TEST\") :\n  -x\n";
+
+/// Runs `SCRIPT` under `interpreter debug a b`, typing `commands` into the
+/// console, and returns the console output and exit code.
+fn debug(name: &str, commands: &str) -> (String, i32) {
+    debug_in(None, name, SCRIPT, commands)
+}
+
+/// Like `debug`, running `script` with `LANG` set to `lang`.
+fn debug_in(lang: Option<&str>, name: &str, script: &str, commands: &str) -> (String, i32) {
+    let path = env::temp_dir().join(format!("rlox-debug-{}-{}.lox", name, std::process::id()));
+    fs::write(&path, script).unwrap();
+
+    let mut command = Command::new(env!("CARGO_BIN_EXE_interpreter"));
+    match lang {
+        Some(lang) => command.env("LANG", lang),
+        None => command.env_remove("LANG"),
+    };
+
+    let mut child = command
+        .arg("debug")
+        .arg(&path)
+        .args(["a", "b"])
+        .env("DEBUG_TEST", "hi")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(commands.as_bytes())
+        .unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    fs::remove_file(&path).unwrap();
+
+    (
+        String::from_utf8(output.stdout).unwrap(),
+        output.status.code().unwrap(),
+    )
+}
+
+#[test]
+fn breakpoints_stepping_and_variables() {
+    let (output, exit_code) = debug("session", "b 2\nset x = 1\nc\ns\nbt\np x + 1\no\n");
+
+    assert_eq!(
+        output,
+        "[line 1] (== x 1)\n\
+         (rlox) Breakpoint on line 2.\n\
+         (rlox) (rlox) [line 2] (+ (+ (call args)  ) (call env DEBUG_TEST))\n\
+         (rlox) [line 2] (+ (call args)  )\n\
+         (rlox) #0 [line 2] (+ (call args)  )\n\
+         #1 [line 2] (+ (+ (call args)  ) (call env DEBUG_TEST))\n\
+         (rlox) Number(2.0)\n\
+         (rlox) String(\"a b hi\")\n"
+    );
+    assert_eq!(exit_code, 0);
+}
+
+#[test]
+fn next_steps_over_subexpressions() {
+    let (output, _) = debug("next", "set x = 2\nn\nn\nq\n");
+
+    assert!(output.contains("(rlox) [line 3] (- x)\n"));
+    assert!(!output.contains("[line 1] x\n"));
+}
+
+#[test]
+fn set_assigns_the_parameter_in_scope() {
+    let (output, _) = debug_in(
+        None,
+        "locals",
+        "((n) =>\n  n + 1\n)(1)\n",
+        "b 2\nc\np n\nset n = 41\nc\n",
+    );
+
+    assert_eq!(
+        output,
+        "[line 3] (call (group (lambda (n) (+ n 1))) 1)\n\
+         (rlox) Breakpoint on line 2.\n\
+         (rlox) [line 2] (+ n 1)\n\
+         (rlox) Number(1.0)\n\
+         (rlox) (rlox) Number(42.0)\n"
+    );
+}
+
+#[test]
+fn quit_stops_the_script() {
+    let (output, exit_code) = debug("quit", "q\n");
+
+    assert_eq!(output, "[line 1] (== x 1)\n(rlox) ");
+    assert_eq!(exit_code, 70);
+}
+
+#[test]
+fn console_speaks_the_language() {
+    let (output, _) = debug_in(
+        Some("pt_BR.UTF-8"),
+        "lang",
+        SCRIPT,
+        "b 2\nd 3\nb x\nfoo\nq\n",
+    );
+
+    assert_eq!(
+        output,
+        "[line 1] (== x 1)\n\
+         (rlox) Ponto de parada na linha 2.\n\
+         (rlox) Nenhum ponto de parada na linha 3.\n\
+         (rlox) Esperado um número de linha.\n\
+         (rlox) Comando desconhecido 'foo'. Tente 'help'.\n\
+         (rlox) "
+    );
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use interpreter::Lox;
+
+fn scripts() -> Vec<PathBuf> {
+    common::collect_scripts(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests"))
+}
+
+#[test]
+fn formatting_is_idempotent() {
+    let lox = Lox::new();
+    let mut formatted_scripts = 0;
+
+    for script in scripts() {
+        let source = fs::read_to_string(&script).unwrap();
+
+        // Scripts that test syntax errors can't be formatted.
+        let Ok(once) = lox.format(&source) else {
+            continue;
+        };
+        let twice = lox.format(&once).unwrap_or_else(|diagnostics| {
+            panic!("{} no longer parses: {:?}", script.display(), diagnostics)
+        });
+
+        assert_eq!(once, twice, "{} is not stable", script.display());
+        formatted_scripts += 1;
+    }
+
+    assert!(formatted_scripts > 0);
+}
+
+#[test]
+fn concrete_syntax_tree_is_lossless() {
+    let lox = Lox::new();
+
+    for script in scripts() {
+        let source = fs::read_to_string(&script).unwrap();
+
+        if let Ok(tree) = lox.parse_cst(&source) {
+            assert_eq!(tree.to_string(), source, "{} changed", script.display());
+        }
+    }
+}
@@ -0,0 +1,161 @@
+mod common;
+
+use std::{fs, path::Path, process::Command};
+
+const EXPECT: &str = "// expect: ";
+const EXPECT_RUNTIME_ERROR: &str = "// expect runtime error: ";
+
+struct Expectation {
+    stdout: Vec<String>,
+    stderr: Vec<String>,
+    exit_code: i32,
+}
+
+impl Expectation {
+    /// `script_frame` is how the suite's interpreter prints the frame of the
+    /// script in the stack trace of a runtime error.
+    fn parse(source: &str, script_frame: &str) -> Self {
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let mut exit_code = 0;
+
+        for (index, line) in source.lines().enumerate() {
+            if let Some(position) = line.find(EXPECT) {
+                stdout.push(line[position + EXPECT.len()..].to_string());
+            } else if let Some(position) = line.find(EXPECT_RUNTIME_ERROR) {
+                stderr.push(line[position + EXPECT_RUNTIME_ERROR.len()..].to_string());
+                stderr.push(format!("[line {}]{}", index + 1, script_frame));
+                exit_code = 70;
+            } else if let Some(position) = line.find("// [line") {
+                let diagnostic = line[position + 3..].to_string();
+                if !diagnostic.contains("] Warning") {
+                    exit_code = 65;
+                }
+                stderr.push(diagnostic);
+            }
+        }
+
+        Self {
+            stdout,
+            stderr,
+            exit_code,
+        }
+    }
+}
+
+struct Suite {
+    dir: &'static str,
+    args: &'static [&'static str],
+    trim_trailing_whitespace: bool,
+    script_frame: &'static str,
+}
+
+impl Suite {
+    fn lines(&self, output: &[u8]) -> Vec<String> {
+        String::from_utf8_lossy(output)
+            .split_terminator('\n')
+            .map(|line| {
+                if self.trim_trailing_whitespace {
+                    line.trim_end().to_string()
+                } else {
+                    line.to_string()
+                }
+            })
+            .collect()
+    }
+
+    fn run(&self) {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join(self.dir);
+
+        let scripts = common::collect_scripts(&root);
+        assert!(
+            !scripts.is_empty(),
+            "No scripts found in {}",
+            root.display()
+        );
+
+        let failures = scripts
+            .iter()
+            .filter_map(|script| self.run_script(script))
+            .collect::<Vec<_>>();
+
+        assert!(
+            failures.is_empty(),
+            "{} of {} scripts failed:\n\n{}",
+            failures.len(),
+            scripts.len(),
+            failures.join("\n")
+        );
+    }
+
+    fn run_script(&self, path: &Path) -> Option<String> {
+        let source = fs::read_to_string(path).unwrap();
+        let expected = Expectation::parse(&source, self.script_frame);
+
+        let output = Command::new(env!("CARGO_BIN_EXE_interpreter"))
+            .env_remove("LANG")
+            .args(self.args)
+            .arg(path)
+            .output()
+            .unwrap();
+
+        let mut failures = vec![
+            diff("stdout", &expected.stdout, &self.lines(&output.stdout)),
+            diff("stderr", &expected.stderr, &self.lines(&output.stderr)),
+        ];
+
+        let exit_code = output.status.code().unwrap_or(-1);
+        if exit_code != expected.exit_code {
+            failures.push(Some(format!(
+                "  exit code: expected {} but got {}\n",
+                expected.exit_code, exit_code
+            )));
+        }
+
+        let failures = failures.into_iter().flatten().collect::<String>();
+
+        if failures.is_empty() {
+            None
+        } else {
+            Some(format!("{}\n{}", path.display(), failures))
+        }
+    }
+}
+
+fn diff(label: &str, expected: &[String], actual: &[String]) -> Option<String> {
+    if expected == actual {
+        return None;
+    }
+
+    let mut report = format!("  {}:\n", label);
+    for line in expected {
+        report.push_str(&format!("    - {}\n", line));
+    }
+    for line in actual {
+        report.push_str(&format!("    + {}\n", line));
+    }
+
+    Some(report)
+}
+
+#[test]
+fn golden_scripts() {
+    Suite {
+        dir: "tests/lox",
+        args: &[],
+        trim_trailing_whitespace: true,
+        script_frame: " in script",
+    }
+    .run();
+}
+
+#[test]
+fn jlox_conformance() {
+    Suite {
+        dir: "tests/jlox",
+        args: &["--compat", "jlox"],
+        trim_trailing_whitespace: false,
+        script_frame: "",
+    }
+    .run();
+}
@@ -0,0 +1,33 @@
+use std::process::{Command, Output};
+
+fn run(max_depth: &str, source: &str) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_interpreter"))
+        .env_remove("LANG")
+        .args(["--max-depth", max_depth, "-e", source])
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn deepest_recursion_fails_with_a_stack_overflow() {
+    let output = run("10000", "((f) => f(f))((f) => f(f))");
+
+    assert_eq!(output.status.code(), Some(70));
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .starts_with("Error[L0311]: Stack overflow.")
+    );
+}
+
+#[test]
+fn max_depth_is_bounded() {
+    let output = run("1000000", "1");
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("must be at most 10000")
+    );
+}
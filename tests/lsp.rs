@@ -0,0 +1,178 @@
+mod common;
+
+use serde_json::{Value, json};
+
+use common::frame;
+
+const URI: &str = "file:///test.lox";
+
+fn request(id: u64, method: &str, params: Value) -> String {
+    frame(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))
+}
+
+fn notification(method: &str, params: Value) -> String {
+    frame(json!({ "jsonrpc": "2.0", "method": method, "params": params }))
+}
+
+fn position(line: u64, character: u64) -> Value {
+    json!({ "textDocument": { "uri": URI }, "position": { "line": line, "character": character } })
+}
+
+/// Sends the messages to `interpreter lsp`, then returns everything it
+/// wrote back and its exit code.
+fn session(messages: &[String]) -> (Vec<Value>, i32) {
+    common::session("lsp", messages)
+}
+
+fn response(responses: &[Value], id: u64) -> &Value {
+    responses
+        .iter()
+        .find(|response| response["id"] == id)
+        .unwrap_or_else(|| panic!("no response to request {}", id))
+}
+
+#[test]
+fn scripted_session() {
+    let (responses, exit_code) = session(&[
+        request(1, "initialize", json!({ "capabilities": {} })),
+        notification("initialized", json!({})),
+        notification(
+            "textDocument/didOpen",
+            json!({ "textDocument": { "uri": URI, "languageId": "lox", "version": 1, "text": "(1 +" } }),
+        ),
+        notification(
+            "textDocument/didChange",
+            json!({
+                "textDocument": { "uri": URI, "version": 2 },
+                "contentChanges": [{ "text": "x == x ?\n  clock() : x" }],
+            }),
+        ),
+        request(2, "textDocument/hover", position(1, 3)),
+        request(3, "textDocument/hover", position(0, 0)),
+        request(4, "textDocument/references", position(0, 5)),
+        request(5, "textDocument/definition", position(0, 0)),
+        request(
+            6,
+            "textDocument/documentSymbol",
+            json!({ "textDocument": { "uri": URI } }),
+        ),
+        request(7, "textDocument/completion", position(1, 2)),
+        request(8, "textDocument/rename", position(0, 0)),
+        request(9, "shutdown", Value::Null),
+        notification("exit", Value::Null),
+    ]);
+
+    assert_eq!(exit_code, 0);
+
+    let capabilities = &response(&responses, 1)["result"]["capabilities"];
+    assert_eq!(capabilities["textDocumentSync"], 1);
+    assert_eq!(capabilities["hoverProvider"], true);
+
+    let published: Vec<&Value> = responses
+        .iter()
+        .filter(|message| message["method"] == "textDocument/publishDiagnostics")
+        .map(|message| &message["params"]["diagnostics"])
+        .collect();
+
+    assert_eq!(published.len(), 2);
+    assert_eq!(published[0][0]["code"], "L0101");
+    assert_eq!(published[0][0]["severity"], 1);
+    assert_eq!(
+        published[1],
+        &json!([{
+            "range": {
+                "start": { "line": 0, "character": 2 },
+                "end": { "line": 0, "character": 4 },
+            },
+            "severity": 2,
+            "code": "L0401",
+            "source": "rlox",
+            "message": "Comparing 'x' with itself.",
+        }])
+    );
+
+    assert_eq!(
+        response(&responses, 2)["result"]["contents"]["value"],
+        "(native function) clock, arity 0"
+    );
+    assert_eq!(
+        response(&responses, 3)["result"]["contents"]["value"],
+        "(undefined) x"
+    );
+
+    let references = response(&responses, 4)["result"].as_array().unwrap();
+    let lines: Vec<&Value> = references
+        .iter()
+        .map(|reference| &reference["range"]["start"]["line"])
+        .collect();
+    assert_eq!(lines, [0, 0, 1]);
+
+    assert_eq!(response(&responses, 5)["result"], Value::Null);
+    assert_eq!(response(&responses, 6)["result"], json!([]));
+
+    let labels: Vec<&str> = response(&responses, 7)["result"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["label"].as_str().unwrap())
+        .collect();
+    assert!(labels.contains(&"while"));
+    assert!(labels.contains(&"clock"));
+    assert!(labels.contains(&"x"));
+
+    assert_eq!(response(&responses, 8)["error"]["code"], -32601);
+    assert_eq!(response(&responses, 9)["result"], Value::Null);
+}
+
+#[test]
+fn exit_without_shutdown_fails() {
+    let (responses, exit_code) = session(&[notification("exit", Value::Null)]);
+
+    assert!(responses.is_empty());
+    assert_eq!(exit_code, 1);
+}
+
+#[test]
+fn parameters_resolve_to_their_lambda() {
+    let text = "((x) => (y) => x + y)(x)";
+    let (responses, _) = session(&[
+        request(1, "initialize", json!({ "capabilities": {} })),
+        notification(
+            "textDocument/didOpen",
+            json!({ "textDocument": { "uri": URI, "languageId": "lox", "version": 1, "text": text } }),
+        ),
+        request(2, "textDocument/hover", position(0, 15)),
+        request(3, "textDocument/hover", position(0, 22)),
+        request(4, "textDocument/definition", position(0, 15)),
+        request(5, "textDocument/definition", position(0, 22)),
+        request(6, "textDocument/references", position(0, 2)),
+        request(7, "textDocument/references", position(0, 22)),
+        request(8, "shutdown", Value::Null),
+        notification("exit", Value::Null),
+    ]);
+
+    let characters = |id: u64| -> Vec<Value> {
+        response(&responses, id)["result"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|reference| reference["range"]["start"]["character"].clone())
+            .collect()
+    };
+
+    assert_eq!(
+        response(&responses, 2)["result"]["contents"]["value"],
+        "(parameter) x"
+    );
+    assert_eq!(
+        response(&responses, 3)["result"]["contents"]["value"],
+        "(undefined) x"
+    );
+    assert_eq!(
+        response(&responses, 4)["result"]["range"]["start"],
+        json!({ "line": 0, "character": 2 })
+    );
+    assert_eq!(response(&responses, 5)["result"], Value::Null);
+    assert_eq!(characters(6), [2, 15]);
+    assert_eq!(characters(7), [22]);
+}
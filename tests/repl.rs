@@ -0,0 +1,121 @@
+use std::{
+    env, fs,
+    io::{BufRead, BufReader, Read, Write},
+    process::{self, Child, ChildStdout, Command, Stdio},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+/// Recurses about 2^60 times, far longer than any test waits.
+const ENDLESS: &str = "((f) => f(f, 60))((f, n) => n < 2 ? n : f(f, n - 1) + f(f, n - 2))";
+
+fn spawn(args: &[&str]) -> Child {
+    Command::new(env!("CARGO_BIN_EXE_interpreter"))
+        .env_remove("LANG")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap()
+}
+
+fn interrupt(child: &Child) {
+    let status = Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+/// Reads `stdout` until what it has read ends with `expected`.
+fn read_until(stdout: &mut ChildStdout, read: &mut String, expected: &str) {
+    let mut byte = [0];
+
+    while !read.ends_with(expected) {
+        assert_eq!(stdout.read(&mut byte).unwrap(), 1, "EOF after {:?}", read);
+        read.push(byte[0] as char);
+    }
+}
+
+#[test]
+fn ctrl_c_at_the_prompt_redraws_it() {
+    let mut child = spawn(&[]);
+    let mut stdout = child.stdout.take().unwrap();
+    let mut read = String::new();
+
+    read_until(&mut stdout, &mut read, "> ");
+    interrupt(&child);
+    read_until(&mut stdout, &mut read, "^C\n> ");
+
+    child.stdin.take().unwrap().write_all(b"1 + 2\n").unwrap();
+    stdout.read_to_string(&mut read).unwrap();
+
+    assert!(child.wait().unwrap().success());
+    assert_eq!(read, "> ^C\n> Number(3.0)\n> ");
+}
+
+#[test]
+fn ctrl_c_while_evaluating_returns_to_the_prompt() {
+    let mut child = spawn(&[]);
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = child.stdout.take().unwrap();
+    let mut read = String::new();
+
+    let (sender, errors) = mpsc::channel();
+    let stderr = BufReader::new(child.stderr.take().unwrap());
+    thread::spawn(move || {
+        for line in stderr.lines() {
+            let _ = sender.send(line.unwrap());
+        }
+    });
+
+    read_until(&mut stdout, &mut read, "> ");
+    writeln!(stdin, "{}", ENDLESS).unwrap();
+
+    // A Ctrl-C that lands before the line is read only redraws the prompt,
+    // so keep pressing it until the evaluation reports the interrupt.
+    let error = loop {
+        interrupt(&child);
+        match errors.recv_timeout(Duration::from_millis(100)) {
+            Ok(line) => break line,
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(err) => panic!("{}", err),
+        }
+    };
+    assert!(error.starts_with("Error[L0307]: Interrupted"), "{}", error);
+
+    writeln!(stdin, "1 + 2").unwrap();
+    drop(stdin);
+    stdout.read_to_string(&mut read).unwrap();
+
+    assert!(child.wait().unwrap().success());
+    assert!(read.ends_with("> Number(3.0)\n> "), "{:?}", read);
+}
+
+#[test]
+fn ctrl_c_in_a_script_exits_with_130() {
+    // Reading a FIFO blocks until it is written, which only happens once the
+    // interpreter has opened it, after it installed its SIGINT handler.
+    let fifo = env::temp_dir().join(format!("rlox-interrupt-{}.lox", process::id()));
+    let _ = fs::remove_file(&fifo);
+    let status = Command::new("mkfifo").arg(&fifo).status().unwrap();
+    assert!(status.success());
+
+    let child = spawn(&[fifo.to_str().unwrap()]);
+    let mut script = fs::OpenOptions::new().write(true).open(&fifo).unwrap();
+    interrupt(&child);
+    script.write_all(ENDLESS.as_bytes()).unwrap();
+    drop(script);
+
+    let output = child.wait_with_output().unwrap();
+    fs::remove_file(&fifo).unwrap();
+
+    assert_eq!(output.status.code(), Some(130));
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .starts_with("Error[L0307]: Interrupted")
+    );
+}